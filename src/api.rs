@@ -0,0 +1,728 @@
+// The HTTP API of `watsup serve`, which gives integrations like dashboards access to the frames,
+// projects, tags, the current state and reports as JSON
+//
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    io::Read,
+    path::Path,
+};
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tiny_http::{Header, Response, Server};
+
+use crate::{
+    cli::{self, FrameFilter},
+    cli_args::{parse_beginning_of_day, parse_end_of_day},
+    common::NonEmptyString,
+    frame::{
+        CompletedFrame, Frame, FrameEditError, FrameReferenceError, FrameStore, find_frame,
+        parse_project,
+    },
+    log::beginning_of_week,
+    project_tree::{ProjectNode, ProjectTree},
+    state::StateStoreBackend,
+    status::Status,
+};
+
+/// Request bodies above this size are rejected
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// The server could not listen on the address
+    Bind(String, String),
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Bind(address, details) => {
+                write!(f, "Could not listen on {}: {}", address, details)
+            }
+        }
+    }
+}
+
+/// A request to the API, independent of the HTTP server
+#[derive(Debug, Default)]
+struct ApiRequest {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    authorization: Option<String>,
+    body: String,
+}
+
+impl ApiRequest {
+    /// Split the URL into the path and the decoded query parameters
+    fn new(method: &str, url: &str, authorization: Option<String>, body: String) -> Self {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (decode_query_component(key), decode_query_component(value))
+            })
+            .collect();
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            query,
+            authorization,
+            body,
+        }
+    }
+
+    fn query_values(&self, key: &str) -> Vec<String> {
+        self.query
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// The frame filter given by the `from`, `to`, `project` and `tag` parameters. Dates are
+    /// RFC 3339 or in the formats of the command line.
+    fn frame_filter(&self) -> Result<FrameFilter, HttpError> {
+        let datetime = |key: &str, parse: fn(&str) -> Result<DateTime<Local>, String>| {
+            self.query_values(key)
+                .last()
+                .map(|value| {
+                    DateTime::parse_from_rfc3339(value)
+                        .map(|datetime| datetime.with_timezone(&Local))
+                        .or_else(|_| parse(value))
+                        .map_err(|e| HttpError::bad_request(format!("Invalid {}: {}", key, e)))
+                })
+                .transpose()
+        };
+        Ok(FrameFilter {
+            from: datetime("from", parse_beginning_of_day)?,
+            to: datetime("to", parse_end_of_day)?,
+            projects: self.query_values("project"),
+            tags: self.query_values("tag"),
+        })
+    }
+}
+
+/// Decode a percent-encoded component of a query string, in which `+` is a space
+fn decode_query_component(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len()
+                && let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok()) =>
+            {
+                decoded.push(byte);
+                i += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[derive(Debug, PartialEq)]
+struct ApiResponse {
+    status: u16,
+    /// `None` for responses without content
+    body: Option<Value>,
+}
+
+impl ApiResponse {
+    fn ok(body: impl Serialize) -> Result<Self, HttpError> {
+        let body = serde_json::to_value(body).map_err(|e| HttpError::internal(e.to_string()))?;
+        Ok(Self {
+            status: 200,
+            body: Some(body),
+        })
+    }
+}
+
+/// An error answered with its status and `{"error": message}`
+#[derive(Debug)]
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(400, message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new(500, message)
+    }
+}
+
+impl From<HttpError> for ApiResponse {
+    fn from(error: HttpError) -> Self {
+        Self {
+            status: error.status,
+            body: Some(json!({ "error": error.message })),
+        }
+    }
+}
+
+impl From<FrameReferenceError> for HttpError {
+    fn from(error: FrameReferenceError) -> Self {
+        match error {
+            FrameReferenceError::NotFound(reference) => {
+                HttpError::new(404, format!("Frame {} not found", reference))
+            }
+            FrameReferenceError::Ambiguous(reference, _) => {
+                HttpError::new(409, format!("Frame id {} is ambiguous", reference))
+            }
+            FrameReferenceError::TooShort(reference) => {
+                HttpError::new(400, format!("Frame id \"{}\" is too short", reference))
+            }
+        }
+    }
+}
+
+impl From<FrameEditError> for HttpError {
+    fn from(error: FrameEditError) -> Self {
+        HttpError::new(422, error.to_string())
+    }
+}
+
+/// A frame as returned by the API
+#[derive(Serialize)]
+struct ApiFrame {
+    id: String,
+    project: String,
+    start: DateTime<Local>,
+    stop: DateTime<Local>,
+    tags: Vec<String>,
+    updated_at: DateTime<Local>,
+}
+
+impl From<&CompletedFrame> for ApiFrame {
+    fn from(completed_frame: &CompletedFrame) -> Self {
+        let frame = completed_frame.frame();
+        Self {
+            id: frame.id().to_string(),
+            project: frame.project().as_str().to_string(),
+            start: *frame.start(),
+            stop: completed_frame.end(),
+            tags: frame.tags().iter().map(|tag| tag.to_string()).collect(),
+            updated_at: frame.last_edit(),
+        }
+    }
+}
+
+/// The body of requests which create or update a frame
+#[derive(Deserialize)]
+struct FrameInput {
+    project: String,
+    start: DateTime<Local>,
+    stop: DateTime<Local>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl FrameInput {
+    fn parse(body: &str) -> Result<Self, HttpError> {
+        serde_json::from_str(body)
+            .map_err(|e| HttpError::bad_request(format!("Invalid frame: {}", e)))
+    }
+
+    /// The frame with the id, or with a new id, checked like an edited frame
+    fn into_frame(self, id: Option<String>) -> Result<CompletedFrame, FrameEditError> {
+        let project = parse_project(&self.project)?;
+        if self.start > self.stop {
+            return Err(FrameEditError::StartAfterStop);
+        }
+        if self.stop > Local::now() {
+            return Err(FrameEditError::StopInFuture);
+        }
+        let tags = self
+            .tags
+            .iter()
+            .filter_map(|tag| NonEmptyString::new(tag.trim()))
+            .collect();
+        let frame = Frame::new(project, id, Some(self.start), Some(self.stop), tags, None);
+        Ok(CompletedFrame::from_frame(frame).expect("The frame has a stop date"))
+    }
+}
+
+/// The time per tag of a project in a report, in the format of `watson report --json`
+#[derive(Serialize)]
+struct TagReport {
+    name: String,
+    /// In seconds
+    time: i64,
+}
+
+#[derive(Serialize)]
+struct ProjectReport {
+    name: String,
+    /// In seconds
+    time: i64,
+    tags: Vec<TagReport>,
+    /// The subprojects, whose time is included in the time of the project
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    projects: Vec<ProjectReport>,
+}
+
+impl ProjectReport {
+    fn new(project: &ProjectNode) -> Self {
+        let mut tags: BTreeMap<&str, Duration> = BTreeMap::new();
+        for frame in project.frames() {
+            for tag in frame.frame().tags() {
+                *tags.entry(tag.as_str()).or_default() += frame.duration();
+            }
+        }
+        let mut subprojects = project.children();
+        subprojects.sort_by_key(|subproject| subproject.name());
+        Self {
+            name: project.name().to_string(),
+            time: project.total().num_seconds(),
+            tags: tags
+                .into_iter()
+                .map(|(name, time)| TagReport {
+                    name: name.to_string(),
+                    time: time.num_seconds(),
+                })
+                .collect(),
+            projects: subprojects.into_iter().map(ProjectReport::new).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Timespan {
+    from: Option<DateTime<Local>>,
+    to: Option<DateTime<Local>>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    timespan: Timespan,
+    /// In seconds
+    time: i64,
+    projects: Vec<ProjectReport>,
+}
+
+impl Report {
+    fn new(frames: &[&CompletedFrame], filter: &FrameFilter) -> Self {
+        let tree = ProjectTree::from_frames(frames.iter().copied());
+        let mut projects = tree.roots();
+        projects.sort_by_key(|project| project.name());
+        Self {
+            timespan: Timespan {
+                from: filter.from,
+                to: filter.to,
+            },
+            time: frames
+                .iter()
+                .map(|f| f.duration())
+                .sum::<Duration>()
+                .num_seconds(),
+            projects: projects.into_iter().map(ProjectReport::new).collect(),
+        }
+    }
+}
+
+/// Compare the tokens in constant time, so the token can not be guessed from response times
+fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Answer the request if it is authorized by the bearer token, if one is required
+fn respond<T: FrameStore + StateStoreBackend>(
+    store: &T,
+    token: Option<&str>,
+    git_dir: Option<&Path>,
+    request: &ApiRequest,
+) -> ApiResponse {
+    if let Some(token) = token {
+        let given = request
+            .authorization
+            .as_deref()
+            .and_then(|authorization| authorization.strip_prefix("Bearer "));
+        if !given.is_some_and(|given| token_matches(token, given.trim())) {
+            return HttpError::new(401, "Missing or invalid bearer token").into();
+        }
+    }
+    log::debug!(
+        "API request. method={} path={}",
+        request.method,
+        request.path
+    );
+    // With auto commit, changes are based on the upstream frames and committed like commands
+    let git_dir = git_dir.filter(|_| request.method != "GET");
+    if let Some(dir) = git_dir {
+        cli::pull_frames(dir, store);
+    }
+    let response = handle(store, request).unwrap_or_else(ApiResponse::from);
+    if let Some(dir) = git_dir
+        && response.status < 300
+    {
+        cli::commit_frames(
+            dir,
+            &format!("watsup serve {} {}", request.method, request.path),
+        );
+    }
+    response
+}
+
+fn handle<T: FrameStore + StateStoreBackend>(
+    store: &T,
+    request: &ApiRequest,
+) -> Result<ApiResponse, HttpError> {
+    let store_error = |e: T::FrameStoreError| HttpError::internal(e.to_string());
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let method = request.method.as_str();
+    let not_allowed = || {
+        Err(HttpError::new(
+            405,
+            format!("Method {} not allowed for {}", method, request.path),
+        ))
+    };
+    match (segments.as_slice(), method) {
+        (["frames"], "GET") => {
+            let filter = request.frame_filter()?;
+            let frames = store.get_all_frames().map_err(store_error)?;
+            let frames: Vec<ApiFrame> = frames
+                .iter()
+                .filter(|frame| filter.matches(frame))
+                .map(ApiFrame::from)
+                .collect();
+            ApiResponse::ok(frames)
+        }
+        (["frames"], "POST") => {
+            let frame = FrameInput::parse(&request.body)?.into_frame(None)?;
+            store
+                .update_frames(std::slice::from_ref(&frame), &[], &[])
+                .map_err(store_error)?;
+            let mut response = ApiResponse::ok(ApiFrame::from(&frame))?;
+            response.status = 201;
+            Ok(response)
+        }
+        (["frames"], _) => not_allowed(),
+        (["frames", reference], "GET" | "PUT" | "DELETE") => {
+            let frames = store.get_all_frames().map_err(store_error)?;
+            let frame = find_frame(&frames, reference)?;
+            let id = frame.frame().id().to_string();
+            match method {
+                "GET" => ApiResponse::ok(ApiFrame::from(frame)),
+                "PUT" => {
+                    let frame = FrameInput::parse(&request.body)?.into_frame(Some(id))?;
+                    store.update_frame(&frame).map_err(store_error)?;
+                    ApiResponse::ok(ApiFrame::from(&frame))
+                }
+                _ => {
+                    store.update_frames(&[], &[], &[id]).map_err(store_error)?;
+                    Ok(ApiResponse {
+                        status: 204,
+                        body: None,
+                    })
+                }
+            }
+        }
+        (["frames", _], _) => not_allowed(),
+        (["projects"], "GET") => {
+            let projects = store.get_projects().map_err(store_error)?;
+            ApiResponse::ok(projects.iter().map(|p| p.as_str()).collect::<Vec<_>>())
+        }
+        (["tags"], "GET") => {
+            let frames = store.get_all_frames().map_err(store_error)?;
+            let tags: BTreeSet<&str> = frames
+                .iter()
+                .flat_map(|frame| frame.frame().tags())
+                .map(|tag| tag.as_str())
+                .collect();
+            ApiResponse::ok(tags)
+        }
+        (["current"], "GET") => {
+            let ongoing_frame = store
+                .get()
+                .map_err(|e| HttpError::internal(e.to_string()))?;
+            let Some(ongoing_frame) = ongoing_frame else {
+                return ApiResponse::ok(Value::Null);
+            };
+            let now = Local::now();
+            let frames = store
+                .get_frames(beginning_of_week(now), now)
+                .map_err(store_error)?;
+            ApiResponse::ok(Status::new(&ongoing_frame, &frames, now))
+        }
+        (["report"], "GET") => {
+            let mut filter = request.frame_filter()?;
+            let now = Local::now();
+            filter.from = filter.from.or(Some(now - Duration::days(7)));
+            filter.to = filter.to.or(Some(now));
+            let frames = store.get_all_frames().map_err(store_error)?;
+            let frames: Vec<&CompletedFrame> = frames
+                .iter()
+                .filter(|frame| filter.matches(frame))
+                .collect();
+            ApiResponse::ok(Report::new(&frames, &filter))
+        }
+        (["projects" | "tags" | "current" | "report"], _) => not_allowed(),
+        _ => Err(HttpError::new(404, format!("Not found: {}", request.path))),
+    }
+}
+
+/// Listen on the address and answer the requests, one at a time. With a token, only requests
+/// with the header `Authorization: Bearer <token>` are answered.
+pub fn serve<T: FrameStore + StateStoreBackend>(
+    store: &T,
+    bind: &str,
+    token: Option<&str>,
+    git_dir: Option<&Path>,
+) -> Result<(), ApiError> {
+    let server = Server::http(bind).map_err(|e| ApiError::Bind(bind.to_string(), e.to_string()))?;
+    println!("Listening on http://{}", server.server_addr());
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("The header is valid");
+    for mut request in server.incoming_requests() {
+        let authorization = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Authorization"))
+            .map(|header| header.value.to_string());
+        let mut body = String::new();
+        let response = match request
+            .as_reader()
+            .take(MAX_BODY_SIZE)
+            .read_to_string(&mut body)
+        {
+            Ok(_) => {
+                let api_request = ApiRequest::new(
+                    request.method().as_str(),
+                    request.url(),
+                    authorization,
+                    body,
+                );
+                respond(store, token, git_dir, &api_request)
+            }
+            Err(e) => HttpError::bad_request(format!("Invalid body: {}", e)).into(),
+        };
+        let body = response
+            .body
+            .map(|body| body.to_string())
+            .unwrap_or_default();
+        let mut http_response = Response::from_string(body).with_status_code(response.status);
+        if response.status != 204 {
+            http_response.add_header(content_type.clone());
+        }
+        if response.status == 401 {
+            http_response.add_header(
+                Header::from_bytes("WWW-Authenticate", "Bearer").expect("The header is valid"),
+            );
+        }
+        if let Err(e) = request.respond(http_response) {
+            log::warn!("Answering API request failed. error={:?}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{state::OngoingFrame, stores::in_memory_store::InMemoryStore};
+
+    fn request(method: &str, url: &str, body: &str) -> ApiRequest {
+        ApiRequest::new(method, url, None, body.to_string())
+    }
+
+    fn create_frame(store: &InMemoryStore, project: &str, tags: &[&str]) -> String {
+        let response = handle(
+            store,
+            &request(
+                "POST",
+                "/frames",
+                &json!({
+                    "project": project,
+                    "start": "2025-01-06T09:00:00+01:00",
+                    "stop": "2025-01-06T10:30:00+01:00",
+                    "tags": tags,
+                })
+                .to_string(),
+            ),
+        )
+        .unwrap();
+        assert_eq!(response.status, 201);
+        response.body.unwrap()["id"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_decode_query_component() {
+        assert_eq!(decode_query_component("a+b%2Fc%3d"), "a b/c=");
+        assert_eq!(decode_query_component("100%"), "100%");
+    }
+
+    #[test]
+    fn test_frames_crud() {
+        let store = InMemoryStore::new();
+        let id = create_frame(&store, "project", &["a"]);
+
+        let response = handle(
+            &store,
+            &request("GET", &format!("/frames/{}", &id[..6]), ""),
+        )
+        .unwrap();
+        assert_eq!(response.body.unwrap()["tags"], json!(["a"]));
+
+        let update = json!({
+            "project": "other",
+            "start": "2025-01-06T09:00:00+01:00",
+            "stop": "2025-01-06T11:00:00+01:00",
+        });
+        let response = handle(
+            &store,
+            &request("PUT", &format!("/frames/{}", id), &update.to_string()),
+        )
+        .unwrap();
+        assert_eq!(response.body.unwrap()["project"], "other");
+        assert_eq!(
+            store.get_frame(&id).unwrap().unwrap().duration(),
+            Duration::hours(2)
+        );
+
+        let response = handle(&store, &request("DELETE", &format!("/frames/{}", id), "")).unwrap();
+        assert_eq!(response.status, 204);
+        let error = handle(&store, &request("GET", &format!("/frames/{}", id), "")).unwrap_err();
+        assert_eq!(error.status, 404);
+    }
+
+    #[test]
+    fn test_invalid_frame_is_rejected() {
+        let store = InMemoryStore::new();
+        let body = json!({
+            "project": "project",
+            "start": "2025-01-06T11:00:00+01:00",
+            "stop": "2025-01-06T10:00:00+01:00",
+        });
+        let error = handle(&store, &request("POST", "/frames", &body.to_string())).unwrap_err();
+        assert_eq!(error.status, 422);
+        let body = json!({
+            "project": "client//area",
+            "start": "2025-01-06T09:00:00+01:00",
+            "stop": "2025-01-06T10:00:00+01:00",
+        });
+        let error = handle(&store, &request("POST", "/frames", &body.to_string())).unwrap_err();
+        assert_eq!(error.status, 422);
+        let error = handle(&store, &request("POST", "/frames", "{")).unwrap_err();
+        assert_eq!(error.status, 400);
+        assert!(store.get_all_frames().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_filter_frames_and_report() {
+        let store = InMemoryStore::new();
+        create_frame(&store, "project", &["a", "b"]);
+        create_frame(&store, "other", &["b"]);
+
+        let response =
+            handle(&store, &request("GET", "/frames?tag=a&from=2025-01-06", "")).unwrap();
+        let frames = response.body.unwrap();
+        assert_eq!(frames.as_array().unwrap().len(), 1);
+        assert_eq!(frames[0]["project"], "project");
+
+        let response = handle(
+            &store,
+            &request("GET", "/report?from=2025-01-01&to=2025-01-31&tag=b", ""),
+        )
+        .unwrap();
+        let report = response.body.unwrap();
+        assert_eq!(report["time"], 2 * 5400);
+        assert_eq!(report["projects"][0]["name"], "other");
+        assert_eq!(
+            report["projects"][1]["tags"][0],
+            json!({"name": "a", "time": 5400})
+        );
+    }
+
+    #[test]
+    fn test_report_nests_subprojects() {
+        let store = InMemoryStore::new();
+        create_frame(&store, "client/web", &[]);
+        create_frame(&store, "client/app", &["a"]);
+
+        let response = handle(&store, &request("GET", "/report?from=2025-01-01", "")).unwrap();
+        let report = response.body.unwrap();
+        let client = &report["projects"][0];
+        assert_eq!(client["name"], "client");
+        assert_eq!(client["time"], 2 * 5400);
+        assert_eq!(client["tags"][0], json!({"name": "a", "time": 5400}));
+        assert_eq!(client["projects"][0]["name"], "client/app");
+        assert_eq!(client["projects"][1]["time"], 5400);
+        assert!(client["projects"][0].get("projects").is_none());
+    }
+
+    #[test]
+    fn test_current_state() {
+        let store = InMemoryStore::new();
+        let response = handle(&store, &request("GET", "/current", "")).unwrap();
+        assert_eq!(response.body, Some(Value::Null));
+
+        let ongoing = OngoingFrame::new(
+            NonEmptyString::new("project").unwrap().into(),
+            Local::now(),
+            vec![],
+        );
+        store.store(&ongoing).unwrap();
+        let response = handle(&store, &request("GET", "/current", "")).unwrap();
+        assert_eq!(response.body.unwrap()["project"], "project");
+    }
+
+    #[test]
+    fn test_bearer_token_is_required() {
+        let store = InMemoryStore::new();
+        let mut request = request("GET", "/projects", "");
+        assert_eq!(respond(&store, Some("secret"), None, &request).status, 401);
+        request.authorization = Some("Bearer wrong".to_string());
+        assert_eq!(respond(&store, Some("secret"), None, &request).status, 401);
+        request.authorization = Some("Bearer secret".to_string());
+        assert_eq!(respond(&store, Some("secret"), None, &request).status, 200);
+    }
+
+    #[test]
+    fn test_unknown_routes() {
+        let store = InMemoryStore::new();
+        assert_eq!(
+            handle(&store, &request("GET", "/unknown", ""))
+                .unwrap_err()
+                .status,
+            404
+        );
+        assert_eq!(
+            handle(&store, &request("PATCH", "/frames", ""))
+                .unwrap_err()
+                .status,
+            405
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, Local};
+
+use crate::frame::{CompletedFrame, Frame};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A problem with the stored frames found by `check_frames`
+#[derive(Debug)]
+pub enum FrameIssue {
+    /// The two frames overlap, the first one starts before (or with) the second one
+    Overlap(CompletedFrame, CompletedFrame),
+    /// The frame ends before or when it starts
+    NonPositiveDuration(CompletedFrame),
+    /// The frame starts or ends after now
+    InFuture(CompletedFrame),
+}
+
+impl Display for FrameIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameIssue::Overlap(first, second) => {
+                let overlap = overlap_duration(first, second);
+                writeln!(
+                    f,
+                    "Overlap of {}m {}s:",
+                    overlap.num_minutes(),
+                    overlap.num_seconds() - overlap.num_minutes() * 60
+                )?;
+                writeln!(
+                    f,
+                    "  {}  {}",
+                    first.frame().start().format(DATE_FORMAT),
+                    first
+                )?;
+                write!(
+                    f,
+                    "  {}  {}",
+                    second.frame().start().format(DATE_FORMAT),
+                    second
+                )
+            }
+            FrameIssue::NonPositiveDuration(frame) => {
+                writeln!(f, "Frame does not end after it starts:")?;
+                write!(
+                    f,
+                    "  {}  {}",
+                    frame.frame().start().format(DATE_FORMAT),
+                    frame
+                )
+            }
+            FrameIssue::InFuture(frame) => {
+                writeln!(f, "Frame is in the future:")?;
+                write!(
+                    f,
+                    "  {}  {}",
+                    frame.frame().start().format(DATE_FORMAT),
+                    frame
+                )
+            }
+        }
+    }
+}
+
+/// The duration in which both frames are running. Zero if the frames do not overlap.
+pub fn overlap_duration(first: &CompletedFrame, second: &CompletedFrame) -> Duration {
+    let start = (*first.frame().start()).max(*second.frame().start());
+    let end = first.end().min(second.end());
+    (end - start).max(Duration::zero())
+}
+
+/// Find all frames in `frames` that overlap with `frame`. The frame itself (same id) is ignored.
+pub fn find_overlaps<'a>(
+    frame: &CompletedFrame,
+    frames: &'a [CompletedFrame],
+) -> Vec<&'a CompletedFrame> {
+    frames
+        .iter()
+        .filter(|other| other.frame().id() != frame.frame().id() && frame.overlaps(other))
+        .collect()
+}
+
+/// Find all issues in the given frames. `frames` must be ordered by start time.
+pub fn check_frames(frames: &[CompletedFrame], now: DateTime<Local>) -> Vec<FrameIssue> {
+    let mut issues = Vec::new();
+    for (index, frame) in frames.iter().enumerate() {
+        if frame.duration() <= Duration::zero() {
+            issues.push(FrameIssue::NonPositiveDuration(frame.clone()));
+        }
+        if *frame.frame().start() > now || frame.end() > now {
+            issues.push(FrameIssue::InFuture(frame.clone()));
+        }
+        // As the frames are ordered by start time, only the following frames which start
+        // before this frame ends can overlap.
+        issues.extend(
+            frames[index + 1..]
+                .iter()
+                .take_while(|other| other.frame().start() < &frame.end())
+                .filter(|other| frame.overlaps(other))
+                .map(|other| FrameIssue::Overlap(frame.clone(), other.clone())),
+        );
+    }
+    issues
+}
+
+/// A way to resolve the overlap of two frames, where the first one starts before (or with) the
+/// second one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapFix {
+    /// End the first frame when the second one starts
+    TrimFirst,
+    /// Start the second frame when the first one ends
+    TrimSecond,
+    /// Cut the second frame out of the first one, which contains it. The part of the first frame
+    /// after the second one becomes a new frame.
+    SplitFirst,
+    KeepBoth,
+}
+
+impl OverlapFix {
+    /// The fixes for the overlap which keep all tracked time in frames with a positive duration
+    pub fn options(first: &CompletedFrame, second: &CompletedFrame) -> Vec<OverlapFix> {
+        let mut options = Vec::new();
+        if second.end() < first.end() {
+            options.push(OverlapFix::SplitFirst);
+        } else if first.frame().start() < second.frame().start() {
+            options.push(OverlapFix::TrimFirst);
+        }
+        if second.end() > first.end() {
+            options.push(OverlapFix::TrimSecond);
+        }
+        options.push(OverlapFix::KeepBoth);
+        options
+    }
+
+    /// The fix of the `options` whose key is the answer, keeping both frames for other answers
+    pub fn from_answer(answer: &str, options: &[OverlapFix]) -> OverlapFix {
+        options
+            .iter()
+            .copied()
+            .find(|option| option.key() == answer)
+            .unwrap_or(OverlapFix::KeepBoth)
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            OverlapFix::TrimFirst => "f",
+            OverlapFix::TrimSecond => "s",
+            OverlapFix::SplitFirst => "p",
+            OverlapFix::KeepBoth => "k",
+        }
+    }
+}
+
+impl Display for OverlapFix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverlapFix::TrimFirst => write!(f, "end [f]irst frame when second starts"),
+            OverlapFix::TrimSecond => write!(f, "start [s]econd frame when first ends"),
+            OverlapFix::SplitFirst => write!(f, "s[p]lit first frame around second"),
+            OverlapFix::KeepBoth => write!(f, "[k]eep both"),
+        }
+    }
+}
+
+/// The frames changed by an `OverlapFix`
+#[derive(Debug, Default)]
+pub struct OverlapChanges {
+    pub first: Option<CompletedFrame>,
+    pub second: Option<CompletedFrame>,
+    /// The part of a split first frame after the second frame
+    pub added: Option<CompletedFrame>,
+}
+
+/// Apply the fix to the overlapping frames
+pub fn fix_overlap(
+    first: &CompletedFrame,
+    second: &CompletedFrame,
+    fix: OverlapFix,
+) -> OverlapChanges {
+    let mut changes = OverlapChanges::default();
+    match fix {
+        OverlapFix::TrimFirst => {
+            let mut first = first.clone();
+            first.set_times(*first.frame().start(), *second.frame().start());
+            changes.first = Some(first);
+        }
+        OverlapFix::TrimSecond => {
+            let mut second = second.clone();
+            second.set_times(first.end(), second.end());
+            changes.second = Some(second);
+        }
+        OverlapFix::SplitFirst => {
+            let mut head = first.clone();
+            if first.frame().start() < second.frame().start() {
+                head.set_times(*first.frame().start(), *second.frame().start());
+                changes.added = CompletedFrame::from_frame(Frame::new(
+                    first.frame().project().clone(),
+                    None,
+                    Some(second.end()),
+                    Some(first.end()),
+                    first.frame().tags().to_vec(),
+                    None,
+                ));
+            } else {
+                // Both frames start at the same time, so only the tail of the first one is left
+                head.set_times(second.end(), first.end());
+            }
+            changes.first = Some(head);
+        }
+        OverlapFix::KeepBoth => {}
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{common::NonEmptyString, frame::Frame};
+
+    fn create_test_frame(start_hour: u32, end_hour: u32) -> CompletedFrame {
+        let start = Local
+            .with_ymd_and_hms(2025, 1, 1, start_hour, 0, 0)
+            .unwrap();
+        let end = Local.with_ymd_and_hms(2025, 1, 1, end_hour, 0, 0).unwrap();
+        let frame = Frame::new(
+            NonEmptyString::new("project").unwrap().into(),
+            None,
+            Some(start),
+            Some(end),
+            vec![],
+            None,
+        );
+        CompletedFrame::from_frame(frame).unwrap()
+    }
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_no_issues_for_adjacent_frames() {
+        let frames = vec![create_test_frame(9, 10), create_test_frame(10, 11)];
+        assert!(check_frames(&frames, now()).is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_frames() {
+        let frames = vec![
+            create_test_frame(9, 12),
+            create_test_frame(10, 11),
+            create_test_frame(11, 13),
+        ];
+        let issues = check_frames(&frames, now());
+        assert_eq!(issues.len(), 2);
+        match &issues[0] {
+            FrameIssue::Overlap(first, second) => {
+                assert_eq!(first.frame().id(), frames[0].frame().id());
+                assert_eq!(second.frame().id(), frames[1].frame().id());
+                assert_eq!(overlap_duration(first, second), Duration::hours(1));
+            }
+            issue => panic!("Expected overlap, got {:?}", issue),
+        }
+    }
+
+    #[test]
+    fn test_non_positive_duration() {
+        let frames = vec![create_test_frame(9, 9), create_test_frame(11, 10)];
+        let issues = check_frames(&frames, now());
+        assert_eq!(issues.len(), 2);
+        assert!(
+            issues
+                .iter()
+                .all(|issue| matches!(issue, FrameIssue::NonPositiveDuration(_)))
+        );
+    }
+
+    #[test]
+    fn test_frame_in_future() {
+        let frames = vec![create_test_frame(9, 10)];
+        let before = Local.with_ymd_and_hms(2025, 1, 1, 9, 30, 0).unwrap();
+        let issues = check_frames(&frames, before);
+        assert!(matches!(issues[..], [FrameIssue::InFuture(_)]));
+    }
+
+    #[test]
+    fn test_find_overlaps_ignores_same_frame() {
+        let frame = create_test_frame(9, 11);
+        let other = create_test_frame(10, 12);
+        let frames = vec![frame.clone(), other.clone(), create_test_frame(11, 12)];
+        let overlaps = find_overlaps(&frame, &frames);
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!(overlaps[0].frame().id(), other.frame().id());
+    }
+
+    fn times(frame: &CompletedFrame) -> (u32, u32) {
+        use chrono::Timelike;
+        (frame.frame().start().hour(), frame.end().hour())
+    }
+
+    #[test]
+    fn test_fix_overlap_by_trimming_first_frame() {
+        let (first, second) = (create_test_frame(9, 11), create_test_frame(10, 12));
+        let options = OverlapFix::options(&first, &second);
+        let fix = OverlapFix::from_answer("f", &options);
+        assert_eq!(fix, OverlapFix::TrimFirst);
+
+        let changes = fix_overlap(&first, &second, fix);
+        let trimmed = changes.first.unwrap();
+        assert_eq!(trimmed.frame().id(), first.frame().id());
+        assert_eq!(times(&trimmed), (9, 10));
+        assert!(changes.second.is_none() && changes.added.is_none());
+    }
+
+    #[test]
+    fn test_fix_overlap_by_trimming_second_frame() {
+        let (first, second) = (create_test_frame(9, 11), create_test_frame(10, 12));
+        let options = OverlapFix::options(&first, &second);
+        let fix = OverlapFix::from_answer("s", &options);
+        assert_eq!(fix, OverlapFix::TrimSecond);
+
+        let changes = fix_overlap(&first, &second, fix);
+        let trimmed = changes.second.unwrap();
+        assert_eq!(trimmed.frame().id(), second.frame().id());
+        assert_eq!(times(&trimmed), (11, 12));
+        assert!(changes.first.is_none() && changes.added.is_none());
+    }
+
+    #[test]
+    fn test_fix_overlap_of_contained_frame_by_splitting() {
+        let (first, second) = (create_test_frame(9, 12), create_test_frame(10, 11));
+        let options = OverlapFix::options(&first, &second);
+        assert_eq!(options, vec![OverlapFix::SplitFirst, OverlapFix::KeepBoth]);
+        // Trimming the first frame would lose the time after the second frame
+        assert_eq!(OverlapFix::from_answer("f", &options), OverlapFix::KeepBoth);
+
+        let changes = fix_overlap(&first, &second, OverlapFix::from_answer("p", &options));
+        let head = changes.first.unwrap();
+        assert_eq!(head.frame().id(), first.frame().id());
+        assert_eq!(times(&head), (9, 10));
+        let tail = changes.added.unwrap();
+        assert_ne!(tail.frame().id(), first.frame().id());
+        assert_eq!(times(&tail), (11, 12));
+        assert_eq!(tail.frame().project(), first.frame().project());
+        assert!(changes.second.is_none());
+    }
+
+    #[test]
+    fn test_fix_overlap_of_contained_frame_with_same_start() {
+        let (first, second) = (create_test_frame(9, 12), create_test_frame(9, 11));
+        let options = OverlapFix::options(&first, &second);
+        assert_eq!(options, vec![OverlapFix::SplitFirst, OverlapFix::KeepBoth]);
+
+        let changes = fix_overlap(&first, &second, OverlapFix::SplitFirst);
+        assert_eq!(times(&changes.first.unwrap()), (11, 12));
+        assert!(changes.added.is_none());
+    }
+}
@@ -1,34 +1,536 @@
+use std::collections::{BTreeSet, HashSet};
 use std::env;
+use std::ffi::OsStr;
+use std::fmt::{Debug, Display};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command as ProcessCommand;
-use std::{fmt::Display, fs::File};
 
 use chrono::{DateTime, Duration, Local};
 use chrono_humanize::HumanTime;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use colored::Colorize;
 use log::info;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use crate::{
+    api,
+    check::{FrameIssue, OverlapFix, check_frames, find_overlaps, fix_overlap},
     common::NonEmptyString,
-    frame::{CompletedFrame, Frame, FrameEdit, FrameStore, ProjectName},
-    log::FrameLog,
+    completions::{self, CompletionShell, CompletionValues},
+    config::{self, Config},
+    edit_format::EditFormat,
+    export::{self, ExportFormat},
+    frame::{
+        BulkFrameEdit, CompletedFrame, Frame, FrameEdit, FrameReferenceError, FrameStore,
+        MIN_ID_PREFIX_LENGTH, ProjectName, ValidFrameEdit, find_frame, is_valid_project_name,
+    },
+    git_repo,
+    git_sync::{self, Divergence},
+    hooks::{self, HookEvent, HookFrame},
+    import::{self, DateOrder, ImportFormat},
+    interactive,
+    log::{FrameLog, beginning_of_day, beginning_of_week, format_duration},
+    merge::{self, Conflict, MergeChanges},
+    project_tree::ProjectTree,
     state::{
-        self, Ongoing, StateStore, StateStoreBackend, StateStoreVariant, Stopped, get_state_store,
+        Ongoing, OngoingFrame, Paused, StateStore, StateStoreBackend, StateStoreVariant, Stopped,
+        WithFrame, get_state_store,
+    },
+    status::{Placeholder, Status, StatusTemplate},
+    sync::{self, CrickClient},
+    tui,
+};
+#[cfg(unix)]
+use crate::{
+    daemon::{
+        self, DaemonError, DaemonRequest, LogParams, RpcError, StartParams, StopParams, StopResult,
     },
+    stores::watson,
 };
+#[cfg(unix)]
+use serde_json::Value;
+
+/// The number of most recent frames whose ids are offered by the shell completion
+const COMPLETED_FRAME_IDS: usize = 20;
+
+/// Lines in the edited file starting with this prefix are ignored
+const EDIT_COMMENT_PREFIX: &str = "#";
+
+/// Remove all comment lines from the content of an edited file
+fn strip_edit_comments(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with(EDIT_COMMENT_PREFIX))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Ask the user a yes/no question on the terminal. Anything but "y" or "yes" is a no.
+fn confirm(question: &str) -> bool {
+    matches!(ask(&format!("{} [y/N]", question)).as_str(), "y" | "yes")
+}
+
+/// The start of a new frame chosen by the answer to the question after an idle gap.
+/// Nothing or "n" is now, "e" is the end of the last frame, anything else a time in between.
+fn parse_idle_gap_answer(
+    answer: &str,
+    last_end: DateTime<Local>,
+    now: DateTime<Local>,
+) -> Result<DateTime<Local>, String> {
+    match answer {
+        "" | "n" | "now" => Ok(now),
+        "e" | "end" => Ok(last_end),
+        time => {
+            let start = crate::cli_args::parse_datetime(time, now.date_naive(), now.time())?;
+            if start < last_end || start > now {
+                return Err(format!(
+                    "The time must be between {} and now",
+                    last_end.format("%Y-%m-%d %H:%M")
+                ));
+            }
+            Ok(start)
+        }
+    }
+}
+
+/// Ask the user a question on the terminal and return the trimmed, lowercase answer.
+/// Returns an empty answer if the terminal can not be read.
+fn ask(question: &str) -> String {
+    print!("{} ", question);
+    if io::stdout().flush().is_err() {
+        return String::new();
+    }
+    let mut answer = String::new();
+    match io::stdin().read_line(&mut answer) {
+        Ok(_) => answer.trim().to_lowercase(),
+        Err(_) => String::new(),
+    }
+}
+
+fn current_dir<E1, E2>() -> Result<PathBuf, CliError<E1, E2>> {
+    env::current_dir().map_err(|e| CliError::RepositoryError(e.to_string()))
+}
+
+/// The project and tags of a frame started in the current directory, see
+/// `git_repo::start_project_and_tags`
+fn start_project_and_tags<E1, E2>(
+    project: Option<&str>,
+    tags: &[String],
+    auto_tag: bool,
+) -> Result<(String, Vec<String>), CliError<E1, E2>> {
+    git_repo::start_project_and_tags(&current_dir()?, project, tags, auto_tag)
+        .map_err(|e| CliError::RepositoryError(e.to_string()))
+}
+
+/// The error for starting a frame while the frame in the state store is ongoing or paused
+fn ongoing_project_error<T: FrameStore + StateStoreBackend>(
+    state_store: StateStoreVariant<T>,
+) -> CliError<T::FrameStoreError, T::StateStoreBackendError> {
+    match state_store.get_ongoing() {
+        Ok(Some(frame)) => CliError::OngoingProject(frame.project().clone()),
+        Ok(None) => CliError::NoOngoingRecording,
+        Err(e) => CliError::StateStoreError(e),
+    }
+}
+
+/// Execute the command with the running daemon and print its result like the command does.
+/// Returns `None` for commands which the daemon does not execute, which then access the files
+/// directly.
+#[cfg(unix)]
+pub fn execute_with_daemon(
+    client: &mut daemon::Client,
+    command: &Command,
+    config: &Config,
+) -> Option<Result<(), CliError<String, String>>> {
+    let request = match command {
+        Command::Start {
+            project,
+            tags,
+            no_gap,
+        } => {
+            // Only the CLI itself can ask for the start after an idle gap
+            if !no_gap && config.get_idle_gap().is_some() && io::stdin().is_terminal() {
+                return None;
+            }
+            // The project file and the repository are those of the current directory of the CLI
+            let (project, tags) =
+                match start_project_and_tags(project.as_deref(), tags, config.get_git().auto_tag) {
+                    Ok(project_and_tags) => project_and_tags,
+                    Err(e) => return Some(Err(e)),
+                };
+            DaemonRequest::Start(StartParams {
+                project,
+                tags,
+                no_gap: *no_gap,
+            })
+        }
+        Command::Stop { at } => DaemonRequest::Stop(StopParams { at: *at }),
+        Command::Status { .. } => DaemonRequest::Status,
+        Command::Log { current, from, to } => DaemonRequest::Log(LogParams {
+            current: *current,
+            from: *from,
+            to: *to,
+        }),
+        _ => return None,
+    };
+    info!("Executing command with the daemon: {:?}", command);
+    let to_error = |error: DaemonError| match error {
+        DaemonError::Rpc(error) => CliError::DaemonError(error.message),
+        error => CliError::DaemonError(format!("Daemon failed: {}", error)),
+    };
+    let result = match command {
+        Command::Start { .. } => client.call::<Status>(&request).map(|status| {
+            println!("Project {} started", status.project.magenta());
+        }),
+        Command::Stop { at } => client.call::<StopResult>(&request).map(|result| {
+            let frames: Vec<CompletedFrame> = result
+                .frames
+                .into_iter()
+                .map(CompletedFrame::from)
+                .collect();
+            let at = at.unwrap_or(Local::now());
+            print_stop(&result.project, &result.start, &at, &frames);
+            let overlaps: Vec<Overlap> = result
+                .overlaps
+                .into_iter()
+                .map(|(frame, other)| (frame.into(), other.into()))
+                .collect();
+            print_overlaps(&overlaps);
+        }),
+        Command::Status { output, quiet } => {
+            return Some(match client.call::<Option<Status>>(&request) {
+                Ok(Some(status)) => output
+                    .print(&status)
+                    .map_err(|e| CliError::SerializationError(e.to_string())),
+                Ok(None) if *quiet => Ok(()),
+                Ok(None) => Err(CliError::NoOngoingRecording),
+                Err(e) => Err(to_error(e)),
+            });
+        }
+        _ => client.call::<Vec<watson::Frame>>(&request).map(|frames| {
+            let frames: Vec<CompletedFrame> =
+                frames.into_iter().map(CompletedFrame::from).collect();
+            print!("{}", FrameLog::new(&frames));
+        }),
+    };
+    Some(result.map_err(to_error))
+}
+
+/// A frame and a stored frame it overlaps
+type Overlap = (CompletedFrame, CompletedFrame);
+
+/// Warn about each frame and the stored frame it overlaps
+fn print_overlaps(overlaps: &[Overlap]) {
+    for (frame, other) in overlaps {
+        println!(
+            "{} frame {} overlaps with frame {}",
+            "Warning:".yellow(),
+            frame,
+            other
+        );
+    }
+}
+
+/// Bring in the frames of the upstream branch of the git repository in the watson directory, so
+/// the commit of a following change is based on them. Without an upstream branch nothing is
+/// pulled. Failures are only reported, e.g. when working offline.
+pub fn pull_frames<T: FrameStore>(dir: &Path, store: &T) {
+    if !git_sync::has_upstream(dir) {
+        return;
+    }
+    match git_sync::pull(dir, store, git_sync::LOCAL_FRAMES_MESSAGE) {
+        Ok((_, Some(changes))) => print_merge_changes(&changes),
+        Ok((_, None)) => {}
+        Err(e) => {
+            log::warn!("Pulling frames failed. error={:?}", e);
+            println!("{} Pulling frames failed: {}", "Warning:".yellow(), e);
+        }
+    }
+}
+
+/// Commit the frames to the git repository in the watson directory. Failures are only reported,
+/// as the frames are saved anyway.
+pub fn commit_frames(dir: &Path, message: &str) {
+    if let Err(e) = git_sync::commit(dir, message) {
+        log::warn!("Committing frames failed. error={:?}", e);
+        println!("{} Committing frames failed: {}", "Warning:".yellow(), e);
+    }
+}
+
+fn print_merge_changes(changes: &MergeChanges) {
+    println!(
+        "Merged frames: {} added, {} updated, {} deleted",
+        changes.insert.len(),
+        changes.update.len(),
+        changes.delete.len()
+    );
+}
+
+/// Print that the frame of `project` which started at `start` was stopped at `at` and saved as
+/// `frames`
+fn print_stop(
+    project: &str,
+    start: &DateTime<Local>,
+    at: &DateTime<Local>,
+    frames: &[CompletedFrame],
+) {
+    println!(
+        "Stopping project {} at {}, started {}",
+        project.magenta(),
+        frames.last().map_or(*at, |frame| frame.end()),
+        start
+    );
+    if frames.len() > 1 {
+        println!(
+            "Saving {} frames, one per segment between the pauses",
+            frames.len()
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+/// The document shown in the editor when editing multiple frames at once.
+/// The frames are wrapped in a table as not all edit formats support a list at the top level.
+struct BulkEditDocument {
+    frames: Vec<BulkFrameEdit>,
+}
+
+#[derive(Default)]
+/// The changes resulting from editing multiple frames at once
+struct FrameChanges {
+    added: Vec<CompletedFrame>,
+    updated: Vec<(CompletedFrame, CompletedFrame)>,
+    deleted: Vec<CompletedFrame>,
+}
+
+impl FrameChanges {
+    /// Compute the changes between the edited `frames` and the validated `edits`.
+    /// Edits without an id are new frames, frames without a matching edit are deleted.
+    fn new(frames: &[CompletedFrame], edits: Vec<(Option<String>, ValidFrameEdit)>) -> Self {
+        let kept_ids: HashSet<String> = edits.iter().filter_map(|(id, _)| id.clone()).collect();
+        let mut changes = FrameChanges {
+            deleted: frames
+                .iter()
+                .filter(|f| !kept_ids.contains(f.frame().id()))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        for (id, edit) in edits {
+            match id.and_then(|id| frames.iter().find(|f| f.frame().id() == id)) {
+                Some(original) => {
+                    let mut frame = original.frame().clone();
+                    frame.update_from(edit);
+                    if FrameEdit::from(&frame) != FrameEdit::from(original.frame()) {
+                        let frame = CompletedFrame::from_frame(frame)
+                            .expect("Validated bulk edits always have a stop date");
+                        changes.updated.push((original.clone(), frame));
+                    }
+                }
+                None => {
+                    let frame = Frame::new(
+                        edit.project().clone(),
+                        None,
+                        Some(edit.start()),
+                        edit.stop(),
+                        edit.tags().to_vec(),
+                        None,
+                    );
+                    let frame = CompletedFrame::from_frame(frame)
+                        .expect("Validated bulk edits always have a stop date");
+                    changes.added.push(frame);
+                }
+            }
+        }
+        changes
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+}
+
+impl Display for FrameChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let date_format = "%Y-%m-%d";
+        for frame in &self.added {
+            let line = format!("+ {}  {}", frame.frame().start().format(date_format), frame);
+            writeln!(f, "{}", line.green())?;
+        }
+        for (original, updated) in &self.updated {
+            let line = format!(
+                "- {}  {}",
+                original.frame().start().format(date_format),
+                original
+            );
+            writeln!(f, "{}", line.red())?;
+            let line = format!(
+                "+ {}  {}",
+                updated.frame().start().format(date_format),
+                updated
+            );
+            writeln!(f, "{}", line.green())?;
+        }
+        for frame in &self.deleted {
+            let line = format!("- {}  {}", frame.frame().start().format(date_format), frame);
+            writeln!(f, "{}", line.red())?;
+        }
+        writeln!(
+            f,
+            "{} added, {} updated, {} deleted",
+            self.added.len(),
+            self.updated.len(),
+            self.deleted.len()
+        )
+    }
+}
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
-    pub command: Command,
+    pub command: Option<Command>,
+}
+
+#[derive(Args, Debug, Default)]
+/// Arguments to select frames
+pub struct FrameFilter {
+    /// Only include frames starting from this date and time on
+    #[arg(short, long, value_parser = crate::cli_args::parse_beginning_of_day)]
+    pub from: Option<DateTime<Local>>,
+    /// Only include frames ending until this date and time
+    #[arg(short, long, value_parser = crate::cli_args::parse_end_of_day)]
+    pub to: Option<DateTime<Local>>,
+    /// Only include frames of this project. Can be given multiple times.
+    #[arg(short, long = "project")]
+    pub projects: Vec<String>,
+    /// Only include frames with this tag. Can be given multiple times, frames need at least one of the tags.
+    #[arg(short = 'T', long = "tag")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+/// The format of an export, given either as positional argument or with `--format`
+pub struct ExportFormatArg {
+    /// The format to export the frames in
+    #[arg(value_enum)]
+    format: Option<ExportFormat>,
+    /// The format to export the frames in, as alternative to the positional argument
+    #[arg(long = "format", value_enum, value_name = "FORMAT")]
+    format_option: Option<ExportFormat>,
+}
+
+impl ExportFormatArg {
+    fn get(&self) -> ExportFormat {
+        self.format
+            .or(self.format_option)
+            .expect("The argument group requires exactly one format")
+    }
+}
+
+impl FrameFilter {
+    pub fn matches(&self, frame: &CompletedFrame) -> bool {
+        self.from.is_none_or(|from| *frame.frame().start() >= from)
+            && self.to.is_none_or(|to| frame.end() <= to)
+            && (self.projects.is_empty()
+                || self
+                    .projects
+                    .iter()
+                    .any(|p| p == frame.frame().project().as_str()))
+            && (self.tags.is_empty()
+                || frame
+                    .frame()
+                    .tags()
+                    .iter()
+                    .any(|tag| self.tags.iter().any(|t| t == tag.as_str())))
+    }
+}
+
+#[derive(Args, Debug, Default)]
+#[group(multiple = false)]
+/// Arguments to choose the output of the status
+pub struct StatusOutput {
+    /// Print the status with a template, e.g. '{project} {elapsed}'.
+    /// Placeholders: {project}, {tags}, {elapsed}, {start}, {today} and {week}
+    #[arg(long, value_parser = crate::status::parse_template, verbatim_doc_comment)]
+    format: Option<StatusTemplate>,
+    /// Print the status as JSON, with durations in seconds
+    #[arg(long)]
+    json: bool,
+    /// Only print the project
+    #[arg(short, long)]
+    project: bool,
+    /// Only print the tags
+    #[arg(short, long)]
+    tags: bool,
+    /// Only print the elapsed time
+    #[arg(short, long)]
+    elapsed: bool,
+}
+
+impl StatusOutput {
+    /// The template of the chosen output. `None` for the default output and JSON.
+    fn template(&self) -> Option<StatusTemplate> {
+        let placeholder = if self.project {
+            Placeholder::Project
+        } else if self.tags {
+            Placeholder::Tags
+        } else if self.elapsed {
+            Placeholder::Elapsed
+        } else {
+            return self.format.clone();
+        };
+        Some(StatusTemplate::placeholder(placeholder))
+    }
+
+    /// Print the status in the chosen output
+    fn print(&self, status: &Status) -> Result<(), serde_json::Error> {
+        if self.json {
+            println!("{}", serde_json::to_string(status)?);
+            return Ok(());
+        }
+        if let Some(template) = self.template() {
+            println!("{}", template.render(status));
+            return Ok(());
+        }
+        let tags = match status.tags.is_empty() {
+            true => String::new(),
+            false => format!(" [{}]", status.tags.join(", ")),
+        };
+        println!(
+            "Project {}{} started {} ({})",
+            status.project.magenta(),
+            tags,
+            HumanTime::from(status.start),
+            status.start
+        );
+        println!(
+            "Elapsed {}, today {}, {} this week {}",
+            format_duration(status.elapsed),
+            format_duration(status.today),
+            status.project.magenta(),
+            format_duration(status.week)
+        );
+        if let Some(paused_since) = status.paused_since {
+            println!(
+                "Paused {} ({})",
+                HumanTime::from(paused_since),
+                paused_since
+            );
+        }
+        Ok(())
+    }
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Start a new frame to record time for a project
     Start {
-        /// The name of the project to track the time for
-        project: String,
+        /// The name of the project to track the time for. Defaults to the project of the
+        /// `.watsup` file in the current directory or its git repository.
+        project: Option<String>,
         /// Tags to associate with the frame
         tags: Vec<String>,
         /// Set the start time of the frame to the end time of the previous frame
@@ -43,18 +545,141 @@ pub enum Command {
     },
     /// Cancel the current frame
     Cancel,
-    /// Edit a frame
+    /// Start a new frame with the project and tags of a previous frame
+    Restart {
+        /// The id of the frame to restart, like for `edit`. Defaults to the last frame.
+        #[arg(allow_negative_numbers = true)]
+        id: Option<String>,
+    },
+    /// Remove a frame
+    Remove {
+        /// The id of the frame to remove, like for `edit`
+        #[arg(allow_negative_numbers = true)]
+        id: String,
+        /// Remove the frame without asking for confirmation
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Pause the current frame, e.g. for an interruption
+    Pause,
+    /// Resume the paused frame
+    Resume,
+    /// Edit a frame, or all frames in a time range when --from, --to or --project is given
     Edit {
         /// The id of the frame to edit.
+        /// Either a unique prefix of at least 4 characters of the id, or the position from the end (-1 for the last frame, -2 for the second to last, ...)
         /// If none provided, and a frame is ongoing, then frame is the currently ongoing frame.
         /// If none provided, and no frame is ongoing, then frame is the last completed frame.
         #[clap(verbatim_doc_comment)]
+        #[arg(allow_negative_numbers = true, conflicts_with_all = ["from", "to", "project"])]
         id: Option<String>,
+        /// Edit all frames from this date and time on. Defaults to the beginning of the current week.
+        #[arg(short, long, value_parser = crate::cli_args::parse_beginning_of_day)]
+        from: Option<DateTime<Local>>,
+        /// Edit all frames until this date and time. Defaults to now.
+        #[arg(short, long, value_parser = crate::cli_args::parse_end_of_day)]
+        to: Option<DateTime<Local>>,
+        /// Only edit frames of this project
+        #[arg(short, long)]
+        project: Option<String>,
+        /// The format in which the frames are shown in the editor. Defaults to the `edit_format` setting.
+        #[arg(long, value_enum)]
+        format: Option<EditFormat>,
     },
     /// List all projects
-    Projects,
+    Projects {
+        /// Show the projects as a tree of their `/`-separated levels, e.g. `client/area`
+        #[arg(long)]
+        tree: bool,
+    },
     /// Show the status of the currently tracked project
-    Status,
+    Status {
+        #[command(flatten)]
+        output: StatusOutput,
+        /// Print nothing instead of an error if no project is started, e.g. for status bars
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Check all frames for overlaps, frames without duration and frames in the future
+    Check {
+        /// Interactively trim overlapping frames
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Import frames from another time tracker
+    Import {
+        /// The format of the file to import
+        #[arg(short, long, value_enum)]
+        format: ImportFormat,
+        /// The file to import
+        file: PathBuf,
+        /// Only show what would be imported, without saving the frames
+        #[arg(long)]
+        dry_run: bool,
+        /// The order of day and month in dates like 03/04/2025. Without it, such dates are only
+        /// imported if just one order gives a valid date.
+        #[arg(long, value_enum)]
+        date_order: Option<DateOrder>,
+    },
+    /// Export frames into the format of another tool
+    Export {
+        #[command(flatten)]
+        format: ExportFormatArg,
+        #[command(flatten)]
+        filter: FrameFilter,
+        /// The file to write the export to. Defaults to the standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Merge the frames file of another watson directory into the frames
+    Merge {
+        /// The frames file to merge
+        file: PathBuf,
+        /// Resolve conflicts without asking by keeping the version which was edited last
+        #[arg(long)]
+        force: bool,
+    },
+    /// Sync the frames with the Crick server configured in the `backend` settings
+    Sync {
+        /// Sync through the git repository in the watson directory instead. Changes of the same
+        /// frame on both sides are merged by keeping the version which was edited last.
+        #[arg(long)]
+        git: bool,
+    },
+    /// Print the completion script for a shell
+    Completions {
+        #[arg(value_enum)]
+        shell: CompletionShell,
+    },
+    /// Print values for the completion scripts, one per line
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        #[arg(value_enum)]
+        values: CompletionValues,
+    },
+    /// Integrate with the git repository of the current directory
+    GitHook {
+        #[command(subcommand)]
+        action: GitHookAction,
+    },
+    /// Show a dashboard of the current frame and the frames of today and this week
+    Tui,
+    /// Show today's frames and pick an action and a project from a menu. This is the default
+    /// when no command is given.
+    Interactive,
+    /// Keep the frames in memory and serve start, stop, status and log on a Unix socket. While
+    /// the daemon runs, these commands are sent to it.
+    #[cfg(unix)]
+    Daemon,
+    /// Serve the frames, projects, tags, the current state and reports as a HTTP/JSON API
+    Serve {
+        /// The address to listen on. Port 0 picks a free port.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
+        /// Only answer requests with the header `Authorization: Bearer <TOKEN>`
+        #[arg(long)]
+        token: Option<String>,
+    },
     /// Show the log of work between provided start and end date
     Log {
         /// Include the currently ongoing frame (if there is one) in the log
@@ -69,21 +694,73 @@ pub enum Command {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum GitHookAction {
+    /// Write a `post-checkout` hook which switches the branch tag of the current frame
+    Install {
+        /// Replace a `post-checkout` hook which was not written by watsup
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Restart the current frame with the tag of the checked out branch, run by the hook
+    #[command(hide = true)]
+    Checkout,
+}
+
+impl Command {
+    /// The name of the command if it changes the stored frames
+    fn frame_changing_name(&self) -> Option<&'static str> {
+        match self {
+            Command::Stop { .. } => Some("stop"),
+            Command::Edit { .. } => Some("edit"),
+            Command::Remove { .. } => Some("remove"),
+            Command::GitHook {
+                action: GitHookAction::Checkout,
+            } => Some("git-hook checkout"),
+            Command::Check { fix: true } => Some("check --fix"),
+            Command::Import { dry_run: false, .. } => Some("import"),
+            Command::Merge { .. } => Some("merge"),
+            Command::Sync { git: false } => Some("sync"),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Any kind of error the CLI ever produces
 /// Errors from the FrameStore and the StateStore are wrapped in the respective errors.
 pub enum CliError<E1, E2> {
     OngoingProject(ProjectName),
+    AlreadyPaused,
+    NotPaused,
     InvalidProjectName,
     FrameStoreError(E1),
     StateStoreError(E2),
     NoOngoingRecording,
     EditorNotSet,
     EditorError(String),
+    EditAborted,
     TempFileError(String),
     SerializationError(String),
     InvalidFrame(Option<String>),
+    AmbiguousFrameId(String, Vec<CompletedFrame>),
+    FrameIdTooShort(String),
+    ImportError(String),
+    ExportError(String),
+    MergeError(String),
+    SyncError(String),
+    PromptError(String),
+    TuiError(String),
+    ApiError(String),
+    RepositoryError(String),
+    /// A `pre-` hook failed, so the command was not executed
+    HookRejected(String),
+    /// The error of a command executed by the daemon, or of the connection to it
+    DaemonError(String),
     FutureStopDate,
+    /// The frame would be stopped before its start or within its first pause, so nothing of it
+    /// would be kept
+    StopBeforeTrackedTime(DateTime<Local>),
 }
 
 impl<E1: Display, E2: Display> Display for CliError<E1, E2> {
@@ -92,6 +769,12 @@ impl<E1: Display, E2: Display> Display for CliError<E1, E2> {
             CliError::OngoingProject(project) => {
                 write!(f, "Project {} already started", project)
             }
+            CliError::AlreadyPaused => {
+                write!(f, "Project already paused")
+            }
+            CliError::NotPaused => {
+                write!(f, "Project is not paused")
+            }
             CliError::InvalidProjectName => {
                 write!(f, "Invalid project name")
             }
@@ -110,6 +793,9 @@ impl<E1: Display, E2: Display> Display for CliError<E1, E2> {
             CliError::EditorError(details) => {
                 write!(f, "Editor error: {}", details)
             }
+            CliError::EditAborted => {
+                write!(f, "Edit aborted, frame left unchanged")
+            }
             CliError::TempFileError(details) => {
                 write!(f, "Temp file error: {}", details)
             }
@@ -123,22 +809,85 @@ impl<E1: Display, E2: Display> Display for CliError<E1, E2> {
                     details.clone().unwrap_or(String::from("No Details"))
                 )
             }
+            CliError::FrameIdTooShort(reference) => write!(
+                f,
+                "Frame id \"{}\" is too short, use at least {} characters of the id",
+                reference, MIN_ID_PREFIX_LENGTH
+            ),
+            CliError::AmbiguousFrameId(reference, candidates) => {
+                write!(f, "Frame id {} is ambiguous. Candidates:", reference)?;
+                for candidate in candidates {
+                    write!(
+                        f,
+                        "\n  {}  {}  {}",
+                        candidate.frame().id(),
+                        candidate.frame().start().format("%Y-%m-%d %H:%M"),
+                        candidate.frame().project()
+                    )?;
+                }
+                Ok(())
+            }
+            CliError::ImportError(details) => {
+                write!(f, "Import failed: {}", details)
+            }
+            CliError::ExportError(details) => {
+                write!(f, "Export failed: {}", details)
+            }
+            CliError::MergeError(details) => {
+                write!(f, "Merge failed: {}", details)
+            }
+            CliError::SyncError(details) => {
+                write!(f, "Sync failed: {}", details)
+            }
+            CliError::PromptError(details) => {
+                write!(f, "Prompt failed: {}", details)
+            }
+            CliError::TuiError(details) => {
+                write!(f, "Dashboard failed: {}", details)
+            }
+            CliError::RepositoryError(details) => {
+                write!(f, "{}", details)
+            }
+            CliError::HookRejected(details) => {
+                write!(f, "Command rejected: {}", details)
+            }
+            CliError::ApiError(details) => {
+                write!(f, "API server failed: {}", details)
+            }
+            CliError::DaemonError(details) => {
+                write!(f, "{}", details)
+            }
             CliError::FutureStopDate => {
                 write!(f, "End date cannot be in the future")
             }
+            CliError::StopBeforeTrackedTime(at) => {
+                write!(
+                    f,
+                    "No time is tracked before {}, stop the frame later or cancel it",
+                    at.format("%Y-%m-%d %H:%M:%S")
+                )
+            }
         }
     }
 }
 
+/// A stopped frame and the frames saved for it, one per segment between its pauses
+struct StoppedFrame {
+    frame: OngoingFrame,
+    frames: Vec<CompletedFrame>,
+}
+
 /// The class responsible for executing commands
 pub struct CommandExecutor<'a, T: FrameStore + StateStoreBackend> {
     /// The place where frames are stored
     store: &'a T,
+    /// The configuration, including the user settings
+    config: Config,
 }
 
 impl<'a, T: FrameStore + StateStoreBackend> CommandExecutor<'a, T> {
-    pub fn new(store: &'a T) -> Self {
-        Self { store }
+    pub fn new(store: &'a T, config: Config) -> Self {
+        Self { store, config }
     }
 
     pub fn execute_command(
@@ -146,63 +895,154 @@ impl<'a, T: FrameStore + StateStoreBackend> CommandExecutor<'a, T> {
         command: &Command,
     ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
         info!("Executing command: {:?}", command);
+        if self.config.get_git().auto_commit && command.frame_changing_name().is_some() {
+            self.pull_frames();
+        }
         let state_store = get_state_store(self.store).map_err(CliError::StateStoreError)?;
-        match command {
+        let result = match command {
             Command::Start {
                 project,
                 tags,
                 no_gap,
             } => match state_store {
-                StateStoreVariant::Ongoing(state_store) => Err(CliError::OngoingProject(
-                    state_store
-                        .get_ongoing()
-                        .map_err(CliError::StateStoreError)?
-                        .project()
-                        .clone(),
-                )),
                 StateStoreVariant::Stopped(state_store) => {
-                    self.start(state_store, project, tags, no_gap)
+                    let (project, tags) = start_project_and_tags(
+                        project.as_deref(),
+                        tags,
+                        self.config.get_git().auto_tag,
+                    )?;
+                    self.start(state_store, &project, &tags, no_gap)
                 }
+                state_store => Err(ongoing_project_error(state_store)),
             },
-            Command::Stop { at } => match state_store {
-                StateStoreVariant::Ongoing(state_store) => {
-                    let stop_datetime = at.unwrap_or(Local::now());
-                    if stop_datetime > Local::now() {
-                        return Err(CliError::FutureStopDate);
+            Command::Stop { at } => {
+                let stop_datetime = at.unwrap_or(Local::now());
+                if stop_datetime > Local::now() {
+                    return Err(CliError::FutureStopDate);
+                }
+                match state_store {
+                    StateStoreVariant::Ongoing(state_store) => {
+                        self.stop(&stop_datetime, state_store)
                     }
-                    self.stop(&stop_datetime, state_store)
+                    StateStoreVariant::Paused(state_store) => {
+                        self.stop(&stop_datetime, state_store)
+                    }
+                    StateStoreVariant::Stopped(_) => Err(CliError::NoOngoingRecording),
                 }
+            }
+            Command::Cancel => match state_store {
+                StateStoreVariant::Ongoing(state_store) => self.cancel(state_store),
+                StateStoreVariant::Paused(state_store) => self.cancel(state_store),
                 StateStoreVariant::Stopped(_) => Err(CliError::NoOngoingRecording),
             },
-            Command::Cancel => match state_store {
-                StateStoreVariant::Ongoing(state_store) => {
-                    let ongoing_frame = state_store
-                        .get_ongoing()
-                        .map_err(CliError::StateStoreError)?;
-                    println!(
-                        "Canceling the timer for project {}",
-                        ongoing_frame.project()
-                    );
-                    state_store.cancel().map_err(CliError::StateStoreError)
-                }
+            Command::Restart { id } => match state_store {
+                StateStoreVariant::Stopped(state_store) => self.restart(state_store, id.as_deref()),
+                state_store => Err(ongoing_project_error(state_store)),
+            },
+            Command::Remove { id, force } => self.remove(id, *force),
+            Command::Pause => match state_store {
+                StateStoreVariant::Ongoing(state_store) => self.pause(state_store),
+                StateStoreVariant::Paused(_) => Err(CliError::AlreadyPaused),
+                StateStoreVariant::Stopped(_) => Err(CliError::NoOngoingRecording),
+            },
+            Command::Resume => match state_store {
+                StateStoreVariant::Paused(state_store) => self.resume(state_store),
+                StateStoreVariant::Ongoing(_) => Err(CliError::NotPaused),
                 StateStoreVariant::Stopped(_) => Err(CliError::NoOngoingRecording),
             },
-            Command::Edit { id } => {
-                if let Some(id) = id {
-                    self.edit(id)
-                } else if let StateStoreVariant::Ongoing(state_store) = state_store {
-                    self.edit_ongoing(&state_store)
-                } else if let Some(f) = self.store.get_last_frame() {
-                    self.edit(f.frame().id())
+            Command::Edit {
+                id,
+                from,
+                to,
+                project,
+                format,
+            } => {
+                let format = format.unwrap_or(self.config.get_edit_format());
+                if from.is_some() || to.is_some() || project.is_some() {
+                    let from = from.unwrap_or(Local::now() - Duration::days(7));
+                    let to = to.unwrap_or(Local::now());
+                    self.edit_bulk(from, to, project.as_deref(), format)
+                } else if let Some(id) = id {
+                    self.edit(id, format)
                 } else {
-                    Err(CliError::InvalidFrame(None))
+                    match state_store {
+                        StateStoreVariant::Ongoing(state_store) => {
+                            self.edit_ongoing(&state_store, format)
+                        }
+                        StateStoreVariant::Paused(state_store) => {
+                            self.edit_ongoing(&state_store, format)
+                        }
+                        StateStoreVariant::Stopped(_) => match self.store.get_last_frame() {
+                            Some(f) => self.edit(f.frame().id(), format),
+                            None => Err(CliError::InvalidFrame(None)),
+                        },
+                    }
                 }
             }
-            Command::Projects => self.list_projects(),
-            Command::Status => match state_store {
-                StateStoreVariant::Ongoing(state_store) => self.status(state_store),
+            Command::Projects { tree } => self.list_projects(*tree),
+            Command::Status { output, quiet } => match state_store {
+                StateStoreVariant::Ongoing(state_store) => self.status(state_store, output),
+                StateStoreVariant::Paused(state_store) => self.status(state_store, output),
+                StateStoreVariant::Stopped(_) if *quiet => Ok(()),
                 StateStoreVariant::Stopped(_) => Err(CliError::NoOngoingRecording),
             },
+            Command::Check { fix } => self.check(*fix),
+            Command::Import {
+                format,
+                file,
+                dry_run,
+                date_order,
+            } => self.import(*format, file, *dry_run, *date_order),
+            Command::Export {
+                format,
+                filter,
+                output,
+            } => self.export(format.get(), filter, output.as_deref()),
+            Command::Merge { file, force } => self.merge(file, *force),
+            Command::Sync { git: false } => self.sync(),
+            Command::Sync { git: true } => self.sync_git(),
+            Command::Completions { shell } => {
+                print!("{}", completions::script(*shell));
+                Ok(())
+            }
+            Command::Complete { values } => self.complete(*values),
+            Command::GitHook {
+                action: GitHookAction::Install { force },
+            } => {
+                let path = git_repo::install_hook(&current_dir()?, *force)
+                    .map_err(|e| CliError::RepositoryError(e.to_string()))?;
+                println!("Installed {}", path.display());
+                Ok(())
+            }
+            Command::GitHook {
+                action: GitHookAction::Checkout,
+            } => match state_store {
+                StateStoreVariant::Ongoing(state_store) => self.switch_branch_tag(state_store),
+                _ => Ok(()),
+            },
+            Command::Interactive => self.interactive(state_store),
+            Command::Tui => {
+                let store = self.store;
+                tui::run(store, |command| {
+                    self.execute_command(command).map_err(|e| e.to_string())
+                })
+                .map_err(|e| CliError::TuiError(e.to_string()))
+            }
+            #[cfg(unix)]
+            Command::Daemon => daemon::serve(&config::get_socket_path(), |request| {
+                self.execute_request(request)
+                    .map_err(|e| RpcError::new(daemon::COMMAND_FAILED, e.to_string()))
+            })
+            .map_err(|e| CliError::DaemonError(format!("Daemon failed: {}", e))),
+            Command::Serve { bind, token } => {
+                let git_dir = self
+                    .config
+                    .get_git()
+                    .auto_commit
+                    .then(|| self.config.get_data_dir());
+                api::serve(self.store, bind, token.as_deref(), git_dir)
+                    .map_err(|e| CliError::ApiError(e.to_string()))
+            }
             Command::Log {
                 current: include_current,
                 from,
@@ -212,166 +1052,1139 @@ impl<'a, T: FrameStore + StateStoreBackend> CommandExecutor<'a, T> {
                 let to = to.unwrap_or(Local::now());
                 self.show_log(from, to, *include_current, state_store)
             }
+        };
+        if result.is_ok()
+            && self.config.get_git().auto_commit
+            && let Some(name) = command.frame_changing_name()
+        {
+            self.commit_frames(&format!("watsup {}", name));
+        }
+        result
+    }
+
+    /// Execute a request of the daemon. Instead of printing, the output is returned as JSON, and
+    /// nothing is asked on the terminal.
+    #[cfg(unix)]
+    fn execute_request(
+        &mut self,
+        request: DaemonRequest,
+    ) -> Result<Value, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        info!("Executing daemon request: {:?}", request);
+        let state_store = get_state_store(self.store).map_err(CliError::StateStoreError)?;
+        let now = Local::now();
+        let to_json = |value: Result<_, serde_json::Error>| {
+            value.map_err(|e| CliError::SerializationError(e.to_string()))
+        };
+        match request {
+            DaemonRequest::Start(params) => {
+                let StateStoreVariant::Stopped(state_store) = state_store else {
+                    return Err(ongoing_project_error(state_store));
+                };
+                let start = match params.no_gap {
+                    true => self.last_frame_end(now),
+                    false => now,
+                };
+                let ongoing_frame =
+                    self.start_frame(state_store, &params.project, &params.tags, start)?;
+                to_json(serde_json::to_value(self.get_status(&ongoing_frame, now)?))
+            }
+            DaemonRequest::Stop(params) => {
+                let at = params.at.unwrap_or(now);
+                if at > now {
+                    return Err(CliError::FutureStopDate);
+                }
+                if self.config.get_git().auto_commit {
+                    self.pull_frames();
+                }
+                let stopped = match state_store {
+                    StateStoreVariant::Ongoing(state_store) => {
+                        self.stop_frames(&at, state_store)?
+                    }
+                    StateStoreVariant::Paused(state_store) => self.stop_frames(&at, state_store)?,
+                    StateStoreVariant::Stopped(_) => return Err(CliError::NoOngoingRecording),
+                };
+                if self.config.get_git().auto_commit {
+                    self.commit_frames("watsup stop");
+                }
+                let overlaps = self.find_overlaps(&stopped.frames)?;
+                to_json(serde_json::to_value(StopResult {
+                    project: stopped.frame.project().as_str().to_string(),
+                    start: *stopped.frame.start(),
+                    frames: stopped.frames.iter().map(watson::Frame::from).collect(),
+                    overlaps: overlaps
+                        .iter()
+                        .map(|(frame, other)| (frame.into(), other.into()))
+                        .collect(),
+                }))
+            }
+            DaemonRequest::Status => {
+                match state_store
+                    .get_ongoing()
+                    .map_err(CliError::StateStoreError)?
+                {
+                    Some(ongoing_frame) => {
+                        to_json(serde_json::to_value(self.get_status(&ongoing_frame, now)?))
+                    }
+                    None => Ok(Value::Null),
+                }
+            }
+            DaemonRequest::Log(params) => {
+                let from = params.from.unwrap_or(now - Duration::days(7));
+                let to = params.to.unwrap_or(now);
+                let frames = self.log_frames(from, to, params.current, state_store)?;
+                let frames: Vec<watson::Frame> = frames.iter().map(watson::Frame::from).collect();
+                to_json(serde_json::to_value(frames))
+            }
+        }
+    }
+
+    /// Bring in the frames of the upstream branch before a command changes the frames
+    fn pull_frames(&self) {
+        pull_frames(self.config.get_data_dir(), self.store);
+    }
+
+    fn commit_frames(&self, message: &str) {
+        commit_frames(self.config.get_data_dir(), message);
+    }
+
+    /// Run the `pre-` hook of the event. A failing hook rejects the command.
+    fn run_pre_hook(
+        &self,
+        event: HookEvent,
+        frame: &HookFrame,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        hooks::run_pre_hook(self.config.get_data_dir(), event, frame)
+            .map_err(|e| CliError::HookRejected(e.to_string()))
+    }
+
+    /// Run the `post-` hook of the event. Failures are only reported, as the command was executed.
+    fn run_post_hook(&self, event: HookEvent, frame: &HookFrame) {
+        if let Err(e) = hooks::run_post_hook(self.config.get_data_dir(), event, frame) {
+            log::warn!("Running post hook failed. error={:?}", e);
+            println!("{} {}", "Warning:".yellow(), e);
         }
     }
 
     fn start(
         &self,
         state_store: StateStore<T, Stopped>,
-        project: &String,
+        project: &str,
         tags: &[String],
         no_gap: &bool,
     ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
-        let project = ProjectName::from(
-            NonEmptyString::new(&project.to_string()).ok_or(CliError::InvalidProjectName)?,
-        );
-        let tags = tags
+        let start = match no_gap {
+            true => self.last_frame_end(Local::now()),
+            false => self.ask_start_after_idle_gap(Local::now()),
+        };
+        let ongoing_frame = self.start_frame(state_store, project, tags, start)?;
+        println!("Project {} started", ongoing_frame.project());
+        Ok(())
+    }
+
+    /// Start a frame of the project at `start` without printing anything
+    fn start_frame(
+        &self,
+        state_store: StateStore<T, Stopped>,
+        project: &str,
+        tags: &[String],
+        start: DateTime<Local>,
+    ) -> Result<OngoingFrame, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let project = ProjectName::new(&self.config.resolve_alias(project))
+            .ok_or(CliError::InvalidProjectName)?;
+        let tags: Vec<NonEmptyString> = tags
             .iter()
             .filter_map(|tag| NonEmptyString::new(tag))
             .collect();
-        let start = match no_gap {
-            true => {
-                log::debug!("--no_gap given, finding last end time");
-                match self.store.get_last_frame() {
-                    Some(frame) => frame.end(),
-                    None => {
-                        log::info!("--no_gap given, but no previous frame. Ignoring --no_gap");
-                        chrono::Local::now()
-                    }
-                }
+        let hook_frame = HookFrame::from(&OngoingFrame::new(project.clone(), start, tags.clone()));
+        self.run_pre_hook(HookEvent::Start, &hook_frame)?;
+        let ongoing_frame = state_store
+            .start(project, start, tags)
+            .map_err(CliError::StateStoreError)?
+            .frame;
+        log::debug!("Starting frame. frame={:?}", ongoing_frame);
+        self.run_post_hook(HookEvent::Start, &HookFrame::from(&ongoing_frame));
+        Ok(ongoing_frame)
+    }
+
+    /// The end of the last frame, for starting without a gap. `now` if there is no frame yet.
+    fn last_frame_end(&self, now: DateTime<Local>) -> DateTime<Local> {
+        log::debug!("--no_gap given, finding last end time");
+        match self.store.get_last_frame() {
+            Some(frame) => frame.end(),
+            None => {
+                log::info!("--no_gap given, but no previous frame. Ignoring --no_gap");
+                now
             }
-            false => chrono::Local::now(),
+        }
+    }
+
+    /// If the last frame ended longer than the `idle_gap_minutes` setting ago, ask whether the new
+    /// frame starts at the end of the last frame, now or at a typed time. Only asks on a terminal.
+    fn ask_start_after_idle_gap(&self, now: DateTime<Local>) -> DateTime<Local> {
+        let (Some(idle_gap), Some(last_frame)) =
+            (self.config.get_idle_gap(), self.store.get_last_frame())
+        else {
+            return now;
+        };
+        let last_end = last_frame.end();
+        let gap = now - last_end;
+        if gap <= idle_gap || !io::stdin().is_terminal() {
+            return now;
+        }
+        println!(
+            "The last frame ({}) ended {}h {}m ago, at {}",
+            last_frame.frame().project(),
+            gap.num_hours(),
+            gap.num_minutes() - gap.num_hours() * 60,
+            last_end.format("%Y-%m-%d %H:%M")
+        );
+        loop {
+            let answer = ask("Start [n]ow, at the [e]nd of the last frame or at a time (HH:MM)?");
+            match parse_idle_gap_answer(&answer, last_end, now) {
+                Ok(start) => return start,
+                Err(e) => println!("{}", e),
+            }
+        }
+    }
+
+    /// Stop the current frame and save one frame per segment between its pauses
+    fn stop<S: WithFrame>(
+        &self,
+        at: &DateTime<Local>,
+        state_store: StateStore<T, S>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let stopped = self.stop_frames(at, state_store)?;
+        print_stop(
+            stopped.frame.project().as_str(),
+            stopped.frame.start(),
+            at,
+            &stopped.frames,
+        );
+        self.warn_overlaps(&stopped.frames)
+    }
+
+    /// Stop the current frame without printing anything
+    fn stop_frames<S: WithFrame>(
+        &self,
+        at: &DateTime<Local>,
+        state_store: StateStore<T, S>,
+    ) -> Result<StoppedFrame, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let ongoing_frame = state_store
+            .get_ongoing()
+            .map_err(CliError::StateStoreError)?;
+        // Stopping would clear the state without any frame to save
+        if !ongoing_frame.can_stop_at(*at) {
+            return Err(CliError::StopBeforeTrackedTime(*at));
+        }
+        let hook_frame = HookFrame::from(&ongoing_frame).stopped_at(*at);
+        self.run_pre_hook(HookEvent::Stop, &hook_frame)?;
+        let frames = state_store
+            .stop(at)
+            .map_err(CliError::StateStoreError)?
+            .frames;
+        self.store
+            .update_frames(&frames, &[], &[])
+            .map_err(CliError::FrameStoreError)?;
+        self.run_post_hook(HookEvent::Stop, &hook_frame);
+        Ok(StoppedFrame {
+            frame: ongoing_frame,
+            frames,
+        })
+    }
+
+    fn cancel<S: WithFrame>(
+        &self,
+        state_store: StateStore<T, S>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let ongoing_frame = state_store
+            .get_ongoing()
+            .map_err(CliError::StateStoreError)?;
+        let hook_frame = HookFrame::from(&ongoing_frame);
+        self.run_pre_hook(HookEvent::Cancel, &hook_frame)?;
+        println!(
+            "Canceling the timer for project {}",
+            ongoing_frame.project()
+        );
+        state_store.cancel().map_err(CliError::StateStoreError)?;
+        self.run_post_hook(HookEvent::Cancel, &hook_frame);
+        Ok(())
+    }
+
+    /// Start a new frame with the project and tags of the referenced frame, or of the last frame
+    fn restart(
+        &self,
+        state_store: StateStore<T, Stopped>,
+        frame_reference: Option<&str>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let frame = match frame_reference {
+            Some(reference) => self.resolve_frame(reference)?,
+            None => self
+                .store
+                .get_last_frame()
+                .ok_or(CliError::InvalidFrame(None))?,
         };
+        let tags: Vec<String> = frame
+            .frame()
+            .tags()
+            .iter()
+            .map(|tag| tag.as_str().to_string())
+            .collect();
+        self.start(state_store, frame.frame().project().as_str(), &tags, &false)
+    }
 
+    /// Stop the current frame and start it again with the tag of the checked out branch, if it is
+    /// tracked in the repository of the current directory
+    fn switch_branch_tag(
+        &self,
+        state_store: StateStore<T, Ongoing>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let Some(repository) = git_repo::Repository::find(&current_dir()?) else {
+            return Ok(());
+        };
         let ongoing_frame = state_store
-            .start(project.clone(), start, tags)
+            .get_ongoing()
+            .map_err(CliError::StateStoreError)?;
+        let tags: Vec<String> = ongoing_frame
+            .tags()
+            .iter()
+            .map(|tag| tag.as_str().to_string())
+            .collect();
+        let Some(tags) = repository.switch_branch_tag(&tags) else {
+            return Ok(());
+        };
+        let now = Local::now();
+        self.stop(&now, state_store)?;
+        let StateStoreVariant::Stopped(state_store) =
+            get_state_store(self.store).map_err(CliError::StateStoreError)?
+        else {
+            return Err(CliError::OngoingProject(ongoing_frame.project().clone()));
+        };
+        let ongoing_frame =
+            self.start_frame(state_store, ongoing_frame.project().as_str(), &tags, now)?;
+        println!(
+            "Project {} started [{}]",
+            ongoing_frame.project(),
+            tags.join(", ")
+        );
+        Ok(())
+    }
+
+    fn remove(
+        &self,
+        frame_reference: &str,
+        force: bool,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let frame = self.resolve_frame(frame_reference)?;
+        if !force && !confirm(&format!("Remove frame {}?", frame)) {
+            return Err(CliError::EditAborted);
+        }
+        self.store
+            .update_frames(&[], &[], &[frame.frame().id().to_string()])
+            .map_err(CliError::FrameStoreError)?;
+        println!("Removed frame {}", frame.frame().id());
+        Ok(())
+    }
+
+    fn pause(
+        &self,
+        state_store: StateStore<T, Ongoing>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let now = Local::now();
+        let frame = state_store
+            .pause(now)
             .map_err(CliError::StateStoreError)?
             .frame;
-        log::debug!("Starting frame. frame={:?}", ongoing_frame);
-        println!("Project {} started", project);
+        println!(
+            "Pausing project {} at {}",
+            frame.project(),
+            now.format("%H:%M")
+        );
+        Ok(())
+    }
+
+    fn resume(
+        &self,
+        state_store: StateStore<T, Paused>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let now = Local::now();
+        let frame = state_store
+            .resume(now)
+            .map_err(CliError::StateStoreError)?
+            .frame;
+        let paused_since = frame.pauses().last().map_or(now, |pause| pause.start);
+        println!(
+            "Resuming project {} after a pause of {}m",
+            frame.project(),
+            (now - paused_since).num_minutes()
+        );
+        Ok(())
+    }
+
+    /// Print a warning for each stored frame that overlaps with one of `frames`
+    fn warn_overlaps(
+        &self,
+        frames: &[CompletedFrame],
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        print_overlaps(&self.find_overlaps(frames)?);
+        Ok(())
+    }
+
+    /// The frames paired with the stored frames they overlap
+    fn find_overlaps(
+        &self,
+        frames: &[CompletedFrame],
+    ) -> Result<Vec<Overlap>, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let all_frames = self
+            .store
+            .get_all_frames()
+            .map_err(CliError::FrameStoreError)?;
+        Ok(frames
+            .iter()
+            .flat_map(|frame| {
+                find_overlaps(frame, &all_frames)
+                    .into_iter()
+                    .map(|other| (frame.clone(), other.clone()))
+            })
+            .collect())
+    }
+
+    /// Open the document in the editor given by the `EDITOR` environment variable and `save` the
+    /// result of `parse` on the edited content.
+    ///
+    /// The document is written to a temp file unique to this invocation. The file is removed once
+    /// the edit was saved or aborted. On any other failure, including a failed save, the file is
+    /// kept and its path printed, so the changes can be recovered.
+    fn edit_in_editor<D, V, R>(
+        document: &D,
+        format: EditFormat,
+        parse: impl Fn(D) -> Result<V, String>,
+        save: impl FnOnce(V) -> Result<R, CliError<T::FrameStoreError, T::StateStoreBackendError>>,
+    ) -> Result<R, CliError<T::FrameStoreError, T::StateStoreBackendError>>
+    where
+        D: Serialize + DeserializeOwned + Debug,
+    {
+        let editor = env::var_os("EDITOR").ok_or(CliError::EditorNotSet)?;
+        let tmp_file = tempfile::Builder::new()
+            .prefix("watsup-edit-")
+            .suffix(format.extension())
+            .tempfile()
+            .map_err(|e| CliError::TempFileError(e.to_string()))?;
+        let result =
+            Self::run_editor(&editor, tmp_file.path(), document, format, parse).and_then(save);
+        match result {
+            Ok(_) | Err(CliError::EditAborted) => {}
+            Err(_) => match tmp_file.keep() {
+                Ok((_, path)) => println!("Your edit was saved in {}", path.display()),
+                Err(e) => log::warn!("Failed to keep temp file. error={}", e),
+            },
+        }
+        result
+    }
+
+    /// Run the editor on the file at `path` until the content can be deserialized and `parse`
+    /// accepts it.
+    ///
+    /// Like `git commit`, the editor is reopened with the error as a comment at the top of the
+    /// file as long as the edit can not be parsed or validated. Emptying the file aborts the edit.
+    fn run_editor<D, V>(
+        editor: &OsStr,
+        path: &Path,
+        document: &D,
+        format: EditFormat,
+        parse: impl Fn(D) -> Result<V, String>,
+    ) -> Result<V, CliError<T::FrameStoreError, T::StateStoreBackendError>>
+    where
+        D: Serialize + DeserializeOwned + Debug,
+    {
+        let mut content = format
+            .serialize(document)
+            .map_err(CliError::SerializationError)?;
+        let mut error: Option<String> = None;
+        loop {
+            let mut buffer = String::new();
+            if let Some(error) = &error {
+                buffer.push_str(&format!("{} Error: {}\n", EDIT_COMMENT_PREFIX, error));
+                buffer.push_str(&format!(
+                    "{} Fix the content below or remove all content to abort the edit.\n",
+                    EDIT_COMMENT_PREFIX
+                ));
+            }
+            buffer.push_str(&content);
+            std::fs::write(path, buffer).map_err(|e| CliError::TempFileError(e.to_string()))?;
+            log::debug!(
+                "Starting editor. editor={:?} path={:?} document={:?} error={:?}",
+                editor,
+                path,
+                document,
+                error
+            );
+            let exit_status = ProcessCommand::new(editor)
+                .arg(path)
+                .status()
+                .map_err(|e| CliError::EditorError(e.to_string()))?;
+            log::debug!("Editor exited. exit_status={:?}", exit_status);
+            if !exit_status.success() {
+                return Err(CliError::EditorError(format!(
+                    "Editor exist status: {}",
+                    exit_status
+                )));
+            }
+
+            content = strip_edit_comments(
+                &std::fs::read_to_string(path)
+                    .map_err(|e| CliError::TempFileError(e.to_string()))?,
+            );
+            if content.trim().is_empty() {
+                return Err(CliError::EditAborted);
+            }
+            match format.deserialize(&content).and_then(&parse) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    log::info!("Invalid edit, reopening editor. error={}", e);
+                    error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Open a single frame in the editor and `save` the validated edit.
+    fn edit_frame_in_editor<R>(
+        frame_edit: &FrameEdit,
+        format: EditFormat,
+        require_stop: bool,
+        save: impl FnOnce(
+            ValidFrameEdit,
+        ) -> Result<R, CliError<T::FrameStoreError, T::StateStoreBackendError>>,
+    ) -> Result<R, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        Self::edit_in_editor(
+            frame_edit,
+            format,
+            |edit: FrameEdit| edit.validate(require_stop).map_err(|e| e.to_string()),
+            save,
+        )
+    }
+
+    /// Get the frame referenced by an id prefix or position, see `find_frame`
+    fn resolve_frame(
+        &self,
+        reference: &str,
+    ) -> Result<CompletedFrame, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        if let Some(frame) = self
+            .store
+            .get_frame(reference)
+            .map_err(CliError::FrameStoreError)?
+        {
+            return Ok(frame);
+        }
+        let frames = self
+            .store
+            .get_all_frames()
+            .map_err(CliError::FrameStoreError)?;
+        match find_frame(&frames, reference) {
+            Ok(frame) => Ok(frame.clone()),
+            Err(FrameReferenceError::NotFound(reference)) => {
+                Err(CliError::InvalidFrame(Some(reference)))
+            }
+            Err(FrameReferenceError::Ambiguous(reference, candidates)) => {
+                Err(CliError::AmbiguousFrameId(reference, candidates))
+            }
+            Err(FrameReferenceError::TooShort(reference)) => {
+                Err(CliError::FrameIdTooShort(reference))
+            }
+        }
+    }
+
+    fn edit(
+        &mut self,
+        frame_reference: &str,
+        format: EditFormat,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let frame = self.resolve_frame(frame_reference)?;
+        let frame_id = frame.frame().id().to_string();
+
+        let completed_frame = Self::edit_frame_in_editor(
+            &FrameEdit::from(frame.frame()),
+            format,
+            true,
+            |updated_frame_edit| {
+                let mut frame = frame.frame().clone();
+                frame.update_from(updated_frame_edit);
+                log::debug!(
+                    "Updated frame successfully. Writing updates to disk. frame={:?}",
+                    frame
+                );
+                let completed_frame = CompletedFrame::from_frame(frame)
+                    .ok_or(CliError::InvalidFrame(Some(frame_id)))?;
+                let hook_frame = HookFrame::from(&completed_frame);
+                self.run_pre_hook(HookEvent::Edit, &hook_frame)?;
+                self.store
+                    .update_frame(&completed_frame)
+                    .map_err(CliError::FrameStoreError)?;
+                self.run_post_hook(HookEvent::Edit, &hook_frame);
+                Ok(completed_frame)
+            },
+        )?;
+        self.warn_overlaps(&[completed_frame])
+    }
+
+    fn edit_ongoing<S: WithFrame>(
+        &self,
+        state_store: &StateStore<T, S>,
+        format: EditFormat,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let mut ongoing_frame = state_store
+            .get_ongoing()
+            .map_err(CliError::StateStoreError)?;
+
+        let frame_edit = FrameEdit::from(&ongoing_frame);
+        Self::edit_frame_in_editor(&frame_edit, format, false, |frame_edit| {
+            ongoing_frame.update_from(frame_edit);
+            let hook_frame = HookFrame::from(&ongoing_frame);
+            self.run_pre_hook(HookEvent::Edit, &hook_frame)?;
+            state_store
+                .update_ongoing(ongoing_frame)
+                .map_err(CliError::StateStoreError)?;
+            self.run_post_hook(HookEvent::Edit, &hook_frame);
+            Ok(())
+        })
+    }
+
+    fn edit_bulk(
+        &mut self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        project: Option<&str>,
+        format: EditFormat,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let frames: Vec<CompletedFrame> = self
+            .store
+            .get_frames(from, to)
+            .map_err(CliError::FrameStoreError)?
+            .into_iter()
+            .filter(|f| project.is_none_or(|p| f.frame().project().as_str() == p))
+            .collect();
+        let document = BulkEditDocument {
+            frames: frames
+                .iter()
+                .map(|f| BulkFrameEdit::from(f.frame()))
+                .collect(),
+        };
+
+        Self::edit_in_editor(
+            &document,
+            format,
+            |document: BulkEditDocument| {
+                let mut seen_ids = HashSet::new();
+                document
+                    .frames
+                    .iter()
+                    .map(|edit| {
+                        if let Some(id) = edit.id() {
+                            if !frames.iter().any(|f| f.frame().id() == id) {
+                                return Err(format!("Unknown frame id {}", id));
+                            }
+                            if !seen_ids.insert(id) {
+                                return Err(format!("Frame {} appears more than once", id));
+                            }
+                        }
+                        let valid_edit =
+                            edit.edit().validate(true).map_err(|e| match edit.id() {
+                                Some(id) => format!("Frame {}: {}", id, e),
+                                None => format!("New frame: {}", e),
+                            })?;
+                        Ok((edit.id().map(String::from), valid_edit))
+                    })
+                    .collect::<Result<Vec<_>, String>>()
+            },
+            |edits| self.apply_bulk_edit(&frames, edits),
+        )
+    }
+
+    /// Show the changes of the bulk edit and save them after a confirmation
+    fn apply_bulk_edit(
+        &mut self,
+        frames: &[CompletedFrame],
+        edits: Vec<(Option<String>, ValidFrameEdit)>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let changes = FrameChanges::new(frames, edits);
+        if changes.is_empty() {
+            println!("No changes");
+            return Ok(());
+        }
+        print!("{}", changes);
+        if !confirm("Apply these changes?") {
+            return Err(CliError::EditAborted);
+        }
+        let updated: Vec<CompletedFrame> = changes
+            .updated
+            .into_iter()
+            .map(|(_, updated)| updated)
+            .collect();
+        let delete: Vec<String> = changes
+            .deleted
+            .iter()
+            .map(|f| f.frame().id().to_string())
+            .collect();
+        // The hooks are run for each added and updated frame
+        let hook_frames: Vec<HookFrame> = changes
+            .added
+            .iter()
+            .chain(&updated)
+            .map(HookFrame::from)
+            .collect();
+        for hook_frame in &hook_frames {
+            self.run_pre_hook(HookEvent::Edit, hook_frame)?;
+        }
+        log::debug!(
+            "Applying bulk edit. insert_count={} update_count={} delete_count={}",
+            changes.added.len(),
+            updated.len(),
+            delete.len()
+        );
+        self.store
+            .update_frames(&changes.added, &updated, &delete)
+            .map_err(CliError::FrameStoreError)?;
+        for hook_frame in &hook_frames {
+            self.run_post_hook(HookEvent::Edit, hook_frame);
+        }
+        self.warn_overlaps(&changes.added)?;
+        self.warn_overlaps(&updated)
+    }
+
+    fn import(
+        &self,
+        format: ImportFormat,
+        file: &Path,
+        dry_run: bool,
+        date_order: Option<DateOrder>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let content = std::fs::read_to_string(file)
+            .map_err(|e| CliError::ImportError(format!("{}: {}", file.display(), e)))?;
+        let parsed = import::parse(format, &content, date_order)
+            .map_err(|e| CliError::ImportError(e.to_string()))?;
+        let existing = self
+            .store
+            .get_all_frames()
+            .map_err(CliError::FrameStoreError)?;
+        let (frames, duplicates) = import::deduplicate(parsed.frames, &existing);
+
+        for frame in &frames {
+            println!("+ {}  {}", frame.frame().start().format("%Y-%m-%d"), frame);
+        }
+        for reason in &parsed.skipped {
+            println!("Skipped: {}", reason);
+        }
+        println!(
+            "{} new frames, {} already tracked, {} skipped",
+            frames.len(),
+            duplicates.len(),
+            parsed.skipped.len()
+        );
+        if dry_run || frames.is_empty() {
+            return Ok(());
+        }
+        log::debug!("Importing frames. frame_count={}", frames.len());
+        self.store
+            .update_frames(&frames, &[], &[])
+            .map_err(CliError::FrameStoreError)?;
+        println!("Imported {} frames", frames.len());
         Ok(())
     }
 
-    fn stop(
+    fn export(
         &self,
-        at: &DateTime<Local>,
-        state_store: StateStore<T, Ongoing>,
+        format: ExportFormat,
+        filter: &FrameFilter,
+        output: Option<&Path>,
     ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
-        let completed_frame = state_store
-            .stop(at)
-            .map_err(CliError::StateStoreError)?
-            .frame;
+        let frames: Vec<CompletedFrame> = self
+            .store
+            .get_all_frames()
+            .map_err(CliError::FrameStoreError)?
+            .into_iter()
+            .filter(|f| filter.matches(f))
+            .collect();
+        log::debug!(
+            "Exporting frames. format={:?} frame_count={}",
+            format,
+            frames.len()
+        );
+        let content =
+            export::export(format, &frames).map_err(|e| CliError::ExportError(e.to_string()))?;
+        match output {
+            Some(path) => std::fs::write(path, content)
+                .map_err(|e| CliError::ExportError(format!("{}: {}", path.display(), e))),
+            None => {
+                print!("{}", content);
+                Ok(())
+            }
+        }
+    }
+
+    fn merge(
+        &self,
+        file: &Path,
+        force: bool,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let content = std::fs::read_to_string(file)
+            .map_err(|e| CliError::MergeError(format!("{}: {}", file.display(), e)))?;
+        let other = merge::parse_watson_frames(&content)
+            .map_err(|e| CliError::MergeError(format!("{}: {}", file.display(), e)))?;
+        if let Some(frame) = other
+            .iter()
+            .find(|frame| !is_valid_project_name(frame.frame().project().as_str()))
+        {
+            return Err(CliError::MergeError(format!(
+                "{}: frame {} has the invalid project \"{}\"",
+                file.display(),
+                frame.frame().id(),
+                frame.frame().project().as_str()
+            )));
+        }
+        let local = self
+            .store
+            .get_all_frames()
+            .map_err(CliError::FrameStoreError)?;
+        let merge = merge::merge_frames(&local, other);
         println!(
-            "Stopping project {} at {}, started {}",
-            completed_frame.frame().project(),
-            completed_frame.end(),
-            completed_frame.frame().start()
+            "{} new frames, {} conflicting, {} unchanged",
+            merge.new.len(),
+            merge.conflicts.len(),
+            merge.unchanged
+        );
+
+        let mut update = Vec::new();
+        for conflict in &merge.conflicts {
+            let keep = if force {
+                conflict.newer()
+            } else {
+                Self::resolve_conflict(conflict)
+            };
+            if !std::ptr::eq(keep, &conflict.local) {
+                update.push(keep.clone());
+            }
+        }
+        if merge.new.is_empty() && update.is_empty() {
+            println!("Nothing to merge");
+            return Ok(());
+        }
+        log::debug!(
+            "Merging frames. new_count={} update_count={}",
+            merge.new.len(),
+            update.len()
         );
         self.store
-            .save_frame(&completed_frame)
+            .update_frames(&merge.new, &update, &[])
             .map_err(CliError::FrameStoreError)?;
-        Ok(())
+        println!(
+            "Added {} frames, updated {} frames",
+            merge.new.len(),
+            update.len()
+        );
+        self.warn_overlaps(&merge.new)?;
+        self.warn_overlaps(&update)
     }
 
-    fn edit_frame_in_editor(
-        frame_edit: &FrameEdit,
-    ) -> Result<FrameEdit, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
-        let editor = env::var_os("EDITOR").ok_or(CliError::EditorNotSet)?;
-        let tmp_file_path = std::env::temp_dir().join("watsup.tmp");
-        let tmp_file_write =
-            File::create(&tmp_file_path).map_err(|e| CliError::TempFileError(e.to_string()))?;
-        serde_json::to_writer_pretty(tmp_file_write, &frame_edit)
-            .map_err(|e| CliError::SerializationError(e.to_string()))?;
-        log::debug!(
-            "Starting editor for editing frame. editor={:?} frame_edit={:?}",
-            editor,
-            frame_edit
+    /// Ask the user which version of a conflicting frame to keep
+    fn resolve_conflict(conflict: &Conflict) -> &CompletedFrame {
+        print!("{}", conflict);
+        match ask("Keep the [l]ocal or the [o]ther frame? [L/o]").as_str() {
+            "o" | "other" => &conflict.other,
+            _ => &conflict.local,
+        }
+    }
+
+    /// Pull the frames changed on the server since the last sync and push the frames edited
+    /// locally since then. A frame edited on both sides keeps the local version, which is pushed.
+    fn sync(&self) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let backend = self.config.get_backend().ok_or_else(|| {
+            CliError::SyncError(
+                "No backend configured, set url and token in the [backend] section of the config file"
+                    .to_string(),
+            )
+        })?;
+        let client = CrickClient::new(backend);
+        let last_sync_path = self.config.get_last_sync_path();
+        let last_sync = sync::read_last_sync(&last_sync_path)
+            .map_err(|e| CliError::SyncError(e.to_string()))?;
+        let local = self
+            .store
+            .get_all_frames()
+            .map_err(CliError::FrameStoreError)?;
+        let push = sync::frames_to_push(&local, last_sync);
+
+        log::debug!("Pulling frames. last_sync={}", last_sync);
+        let pulled = client
+            .pull(last_sync)
+            .map_err(|e| CliError::SyncError(e.to_string()))?;
+        let merge = merge::merge_frames(&local, pulled);
+        let pending: HashSet<&str> = push.iter().map(|f| f.frame().id()).collect();
+        let update: Vec<CompletedFrame> = merge
+            .conflicts
+            .into_iter()
+            .filter(|conflict| !pending.contains(conflict.local.frame().id()))
+            .map(|conflict| conflict.other)
+            .collect();
+        if !merge.new.is_empty() || !update.is_empty() {
+            self.store
+                .update_frames(&merge.new, &update, &[])
+                .map_err(CliError::FrameStoreError)?;
+        }
+
+        if !push.is_empty() {
+            log::debug!("Pushing frames. frame_count={}", push.len());
+            let skipped = client
+                .push(&push)
+                .map_err(|e| CliError::SyncError(e.to_string()))?;
+            for frame in &skipped {
+                println!(
+                    "{} Frame {} was not pushed, its id is not a UUID",
+                    "Warning:".yellow(),
+                    frame.frame().id()
+                );
+            }
+        }
+        sync::write_last_sync(&last_sync_path, chrono::Utc::now())
+            .map_err(|e| CliError::SyncError(e.to_string()))?;
+        println!(
+            "Pulled {} frames, pushed {} frames",
+            merge.new.len() + update.len(),
+            push.len()
         );
-        let exit_status = ProcessCommand::new(editor)
-            .arg(&tmp_file_path)
-            .status()
-            .map_err(|e| CliError::EditorError(e.to_string()))?;
-        let tmp_file_read =
-            File::open(&tmp_file_path).map_err(|e| CliError::TempFileError(e.to_string()))?;
-        let updated_frame_edit: FrameEdit = serde_json::from_reader(tmp_file_read)
-            .map_err(|e| CliError::SerializationError(e.to_string()))?;
-        log::debug!("Editor exited. exit_status={:?}", exit_status);
+        Ok(())
+    }
 
-        match exit_status.success() {
-            true => Ok(updated_frame_edit),
-            false => Err(CliError::EditorError(format!(
-                "Editor exist status: {}",
-                exit_status
-            ))),
+    /// Commit the local frames, merge the frames of the upstream branch and push the result
+    fn sync_git(&self) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let dir = self.config.get_data_dir();
+        let git_error = |e: git_sync::GitError| CliError::SyncError(e.to_string());
+        let (divergence, changes) =
+            git_sync::pull(dir, self.store, "watsup sync").map_err(git_error)?;
+        if divergence == Divergence::UpToDate {
+            println!("Frames are up to date");
+            return Ok(());
+        }
+        if let Some(changes) = changes {
+            print_merge_changes(&changes);
         }
+        git_sync::push(dir).map_err(git_error)?;
+        println!("Frames synced");
+        Ok(())
     }
 
-    fn edit(
-        &mut self,
-        frame_id: &str,
+    fn check(
+        &self,
+        fix: bool,
     ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
-        let frame = self
+        let frames = self
             .store
-            .get_frame(frame_id)
-            .map_err(CliError::FrameStoreError)?
-            .ok_or(CliError::InvalidFrame(Some(frame_id.into())))?;
-
-        let updated_frame_edit = Self::edit_frame_in_editor(&FrameEdit::from(frame.frame()))?;
+            .get_all_frames()
+            .map_err(CliError::FrameStoreError)?;
+        let issues = check_frames(&frames, Local::now());
+        if issues.is_empty() {
+            println!("No issues found");
+            return Ok(());
+        }
+        for issue in &issues {
+            println!("{}", issue);
+        }
+        println!("Found {} issue(s)", issues.len());
+        if fix
+            && issues
+                .iter()
+                .any(|i| matches!(i, FrameIssue::Overlap(_, _)))
+        {
+            self.fix_overlaps(frames)?;
+        }
+        Ok(())
+    }
 
-        let mut frame = frame.frame().clone();
-        frame.update_from(updated_frame_edit);
+    /// Go through all overlapping frames and ask the user how to trim or split them.
+    /// All changes are written to the store at once after the last overlap.
+    fn fix_overlaps(
+        &self,
+        mut frames: Vec<CompletedFrame>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let first_added = frames.len();
+        let mut changed = HashSet::new();
+        for first_index in 0..frames.len() {
+            // Split off frames are appended, so they are checked against the later frames
+            for second_index in first_index + 1..frames.len() {
+                let (first, second) = (&frames[first_index], &frames[second_index]);
+                if !first.overlaps(second) {
+                    continue;
+                }
+                println!("{}", FrameIssue::Overlap(first.clone(), second.clone()));
+                let options = OverlapFix::options(first, second);
+                let question: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+                let answer = ask(&format!("{}?", question.join(", ")));
+                let fix = OverlapFix::from_answer(&answer, &options);
+                if fix == OverlapFix::KeepBoth {
+                    println!("Keeping both frames");
+                    continue;
+                }
+                let changes = fix_overlap(first, second, fix);
+                if let Some(first) = changes.first {
+                    frames[first_index] = first;
+                    changed.insert(first_index);
+                }
+                if let Some(second) = changes.second {
+                    frames[second_index] = second;
+                    changed.insert(second_index);
+                }
+                if let Some(added) = changes.added {
+                    frames.push(added);
+                }
+            }
+        }
+        let added = frames.split_off(first_added);
+        if changed.is_empty() {
+            return Ok(());
+        }
+        // Changes of split off frames are part of the added frames already
+        let save: Vec<CompletedFrame> = changed
+            .into_iter()
+            .filter(|&i| i < first_added)
+            .map(|i| frames[i].clone())
+            .collect();
         log::debug!(
-            "Updated frame successfully. Writing updates to disk. frame={:?}",
-            frame
+            "Fixing overlapping frames. update_count={} insert_count={}",
+            save.len(),
+            added.len()
         );
         self.store
-            .save_frame(&CompletedFrame::from_frame(frame).unwrap())
-            .map_err(CliError::FrameStoreError)
+            .update_frames(&added, &save, &[])
+            .map_err(CliError::FrameStoreError)?;
+        println!("Trimmed {} frames", save.len());
+        if !added.is_empty() {
+            println!("Split off {} frames", added.len());
+        }
+        Ok(())
     }
 
-    fn edit_ongoing(
+    fn complete(
         &self,
-        state_store: &StateStore<T, state::Ongoing>,
+        values: CompletionValues,
     ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
-        let mut ongoing_frame = state_store
-            .get_ongoing()
-            .map_err(CliError::StateStoreError)?;
-
-        let frame_edit = FrameEdit::from(&ongoing_frame);
-        let frame_edit = Self::edit_frame_in_editor(&frame_edit)?;
-
-        ongoing_frame.update_from(frame_edit);
-        state_store
-            .update_ongoing(ongoing_frame)
-            .map_err(CliError::StateStoreError)
+        let values: Vec<String> = match values {
+            CompletionValues::Projects => self
+                .store
+                .get_projects()
+                .map_err(CliError::FrameStoreError)?
+                .iter()
+                .map(|project| project.as_str().to_string())
+                .collect(),
+            CompletionValues::Tags => {
+                let frames = self
+                    .store
+                    .get_all_frames()
+                    .map_err(CliError::FrameStoreError)?;
+                let tags: BTreeSet<&str> = frames
+                    .iter()
+                    .flat_map(|frame| frame.frame().tags().iter().map(|tag| tag.as_str()))
+                    .collect();
+                tags.into_iter().map(String::from).collect()
+            }
+            CompletionValues::Ids => self
+                .store
+                .get_all_frames()
+                .map_err(CliError::FrameStoreError)?
+                .iter()
+                .rev()
+                .take(COMPLETED_FRAME_IDS)
+                .map(|frame| frame.frame().id().to_string())
+                .collect(),
+        };
+        for value in values {
+            println!("{}", value);
+        }
+        Ok(())
     }
 
-    fn list_projects(&self) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+    fn list_projects(
+        &self,
+        tree: bool,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
         let projects = self
             .store
             .get_projects()
             .map_err(CliError::FrameStoreError)?;
+        if tree {
+            print!("{}", ProjectTree::from_projects(&projects));
+            return Ok(());
+        }
         for project in projects {
             println!("{}", project);
         }
         Ok(())
     }
 
-    fn status(
+    fn status<S: WithFrame>(
         &self,
-        state_store: StateStore<T, Ongoing>,
+        state_store: StateStore<T, S>,
+        output: &StatusOutput,
     ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
         let ongoing_frame = state_store
             .get_ongoing()
             .map_err(CliError::StateStoreError)?;
-        let frame = Frame::from(ongoing_frame);
-        let completed_frame = frame.set_end(Local::now());
-        println!(
-            "Project {} started {} ({})",
-            completed_frame.frame().project(),
-            HumanTime::from(*completed_frame.frame().start()),
-            completed_frame.frame().start()
-        );
+        let status = self.get_status(&ongoing_frame, Local::now())?;
+        output
+            .print(&status)
+            .map_err(|e| CliError::SerializationError(e.to_string()))
+    }
+
+    /// The status of the ongoing frame at `now`, with the totals of this week's frames
+    fn get_status(
+        &self,
+        ongoing_frame: &OngoingFrame,
+        now: DateTime<Local>,
+    ) -> Result<Status, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let frames = self
+            .store
+            .get_frames(beginning_of_week(now), now)
+            .map_err(CliError::FrameStoreError)?;
+        Ok(Status::new(ongoing_frame, &frames, now))
+    }
+
+    /// Show today's frames including the current one, then execute the commands of the action
+    /// picked from the menu. Stops at the first failing command.
+    fn interactive(
+        &mut self,
+        state_store: StateStoreVariant<T>,
+    ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let now = Local::now();
+        self.show_log(beginning_of_day(now), now, true, state_store)?;
+        // The picker can not read keys from a pipe and would redraw forever
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return Err(CliError::PromptError(
+                "The interactive menu needs a terminal".to_string(),
+            ));
+        }
+
+        let Some(action) =
+            interactive::pick_action().map_err(|e| CliError::PromptError(e.to_string()))?
+        else {
+            return Ok(());
+        };
+        let project = match action.needs_project() {
+            true => {
+                let projects = self
+                    .store
+                    .get_projects()
+                    .map_err(CliError::FrameStoreError)?;
+                let project = interactive::pick_project(&projects)
+                    .map_err(|e| CliError::PromptError(e.to_string()))?;
+                if project.is_none() {
+                    println!("No project selected");
+                    return Ok(());
+                }
+                project
+            }
+            false => None,
+        };
+        for command in action.commands(project.as_deref()) {
+            self.execute_command(&command)?;
+        }
         Ok(())
     }
 
@@ -382,22 +2195,32 @@ impl<'a, T: FrameStore + StateStoreBackend> CommandExecutor<'a, T> {
         include_current: bool,
         state_store: StateStoreVariant<T>,
     ) -> Result<(), CliError<T::FrameStoreError, T::StateStoreBackendError>> {
+        let frames = self.log_frames(from, to, include_current, state_store)?;
+        print!("{}", FrameLog::new(&frames));
+        Ok(())
+    }
+
+    /// The frames between `from` and `to`, including the current frame up to now if requested
+    fn log_frames(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        include_current: bool,
+        state_store: StateStoreVariant<T>,
+    ) -> Result<Vec<CompletedFrame>, CliError<T::FrameStoreError, T::StateStoreBackendError>> {
         let mut frames = self
             .store
             .get_frames(from, to)
             .map_err(CliError::FrameStoreError)?;
 
-        if include_current && let StateStoreVariant::Ongoing(state_store) = state_store {
-            let ongoing_frame = state_store
+        if include_current
+            && let Some(ongoing_frame) = state_store
                 .get_ongoing()
-                .map_err(CliError::StateStoreError)?;
-            let frame = Frame::from(ongoing_frame).set_end(Local::now());
-            frames.push(frame);
+                .map_err(CliError::StateStoreError)?
+        {
+            frames.extend(ongoing_frame.complete(Local::now()));
         }
-
-        let log = FrameLog::new(&frames);
-        print!("{}", log);
-        Ok(())
+        Ok(frames)
     }
 }
 
@@ -406,13 +2229,324 @@ mod tests {
     use super::*;
     use crate::stores::in_memory_store::InMemoryStore;
 
+    fn make_completed_frame(project: &str, start_hour: u32) -> CompletedFrame {
+        use chrono::TimeZone;
+        let start = Local
+            .with_ymd_and_hms(2025, 1, 1, start_hour, 0, 0)
+            .unwrap();
+        Frame::new(
+            ProjectName::from(NonEmptyString::new(project).unwrap()),
+            None,
+            Some(start),
+            None,
+            vec![],
+            None,
+        )
+        .set_end(start + Duration::hours(1))
+    }
+
+    fn make_bulk_edits(json: &str) -> Vec<(Option<String>, ValidFrameEdit)> {
+        serde_json::from_str::<Vec<BulkFrameEdit>>(json)
+            .unwrap()
+            .iter()
+            .map(|e| (e.id().map(String::from), e.edit().validate(true).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_frame_changes_detects_added_updated_and_deleted_frames() {
+        let unchanged = make_completed_frame("unchanged", 9);
+        let updated = make_completed_frame("updated", 10);
+        let deleted = make_completed_frame("deleted", 11);
+        let frames = vec![unchanged.clone(), updated.clone(), deleted];
+
+        let mut document: Vec<serde_json::Value> = frames[..2]
+            .iter()
+            .map(|f| serde_json::to_value(BulkFrameEdit::from(f.frame())).unwrap())
+            .collect();
+        document[1]["project"] = "renamed".into();
+        document.push(serde_json::json!({
+            "project": "added",
+            "start": "2025-01-01 12:00:00",
+            "stop": "2025-01-01 13:00:00",
+            "tags": []
+        }));
+        let edits = make_bulk_edits(&serde_json::to_string(&document).unwrap());
+
+        let changes = FrameChanges::new(&frames, edits);
+
+        assert_eq!(changes.added.len(), 1);
+        assert_eq!(changes.added[0].frame().project().as_str(), "added");
+        assert_eq!(changes.updated.len(), 1);
+        assert_eq!(changes.updated[0].0.frame().id(), updated.frame().id());
+        assert_eq!(changes.updated[0].1.frame().project().as_str(), "renamed");
+        assert_eq!(changes.deleted.len(), 1);
+        assert_eq!(changes.deleted[0].frame().project().as_str(), "deleted");
+    }
+
+    #[test]
+    fn test_frame_changes_without_edits_is_empty() {
+        let frames = vec![make_completed_frame("project", 9)];
+        let document: Vec<BulkFrameEdit> = frames
+            .iter()
+            .map(|f| BulkFrameEdit::from(f.frame()))
+            .collect();
+        let edits = make_bulk_edits(&serde_json::to_string(&document).unwrap());
+
+        assert!(FrameChanges::new(&frames, edits).is_empty());
+    }
+
+    #[test]
+    fn test_edit_with_ambiguous_id_returns_error() {
+        let store = InMemoryStore::new();
+        for (id, hour) in [("abcd1", 9), ("abcd2", 10)] {
+            let start = make_completed_frame("project", hour);
+            let frame = Frame::new(
+                start.frame().project().clone(),
+                Some(id.to_string()),
+                Some(*start.frame().start()),
+                Some(start.end()),
+                vec![],
+                None,
+            );
+            store
+                .insert_frame(&CompletedFrame::from_frame(frame).unwrap())
+                .unwrap();
+        }
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        let result = executor.execute_command(&Command::Edit {
+            id: Some("abcd".to_string()),
+            from: None,
+            to: None,
+            project: None,
+            format: None,
+        });
+
+        match result {
+            Err(CliError::AmbiguousFrameId(_, candidates)) => assert_eq!(candidates.len(), 2),
+            _ => panic!("Expected AmbiguousFrameId error"),
+        }
+    }
+
+    #[test]
+    fn test_import_skips_already_tracked_frames() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("export.json");
+        std::fs::write(
+            &file,
+            r#"[{"start":"20250101T090000Z","end":"20250101T100000Z","tags":["project"]}]"#,
+        )
+        .unwrap();
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+        let command = Command::Import {
+            format: ImportFormat::Timewarrior,
+            file,
+            dry_run: false,
+            date_order: None,
+        };
+
+        executor.execute_command(&command).unwrap();
+        executor.execute_command(&command).unwrap();
+
+        assert_eq!(store.get_all_frames().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_import_dry_run_saves_nothing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("export.json");
+        std::fs::write(
+            &file,
+            r#"[{"start":"20250101T090000Z","end":"20250101T100000Z","tags":["project"]}]"#,
+        )
+        .unwrap();
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        let result = executor.execute_command(&Command::Import {
+            format: ImportFormat::Timewarrior,
+            file,
+            dry_run: true,
+            date_order: None,
+        });
+
+        assert!(result.is_ok());
+        assert!(store.get_all_frames().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_rejects_invalid_project() {
+        let store = InMemoryStore::new();
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("frames");
+        std::fs::write(
+            &file,
+            r#"[[1700000000, 1700003600, "client//area", "id", [], 1700003600]]"#,
+        )
+        .unwrap();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        let result = executor.execute_command(&Command::Merge { file, force: true });
+
+        assert!(matches!(result, Err(CliError::MergeError(_))));
+        assert!(store.get_all_frames().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_with_force_keeps_newer_frames() {
+        let store = InMemoryStore::new();
+        let local = make_completed_frame("local", 9);
+        store.insert_frame(&local).unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("frames");
+        // The conflicting frame was edited in 2100, so it is newer than the local one
+        std::fs::write(
+            &file,
+            format!(
+                r#"[[1700000000, 1700003600, "other", "{}", [], 4102444800],
+                    [1700010000, 1700013600, "new", "new-id", [], 1700013600]]"#,
+                local.frame().id()
+            ),
+        )
+        .unwrap();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        executor
+            .execute_command(&Command::Merge { file, force: true })
+            .unwrap();
+
+        let frames = store.get_all_frames().unwrap();
+        assert_eq!(frames.len(), 2);
+        let merged = store.get_frame(local.frame().id()).unwrap().unwrap();
+        assert_eq!(merged.frame().project().as_str(), "other");
+    }
+
+    #[test]
+    fn test_frame_filter() {
+        let frame = make_completed_frame("project", 9);
+        let filter = |projects: &[&str], tags: &[&str]| FrameFilter {
+            projects: projects.iter().map(|p| p.to_string()).collect(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+
+        assert!(filter(&[], &[]).matches(&frame));
+        assert!(filter(&["other", "project"], &[]).matches(&frame));
+        assert!(!filter(&["other"], &[]).matches(&frame));
+        assert!(!filter(&[], &["tag"]).matches(&frame));
+        assert!(
+            !FrameFilter {
+                from: Some(*frame.frame().start() + Duration::minutes(1)),
+                ..Default::default()
+            }
+            .matches(&frame)
+        );
+    }
+
+    #[test]
+    fn test_export_format_as_argument_or_option() {
+        for args in [["export", "ics"], ["export", "--format=ics"]] {
+            let cli = Cli::try_parse_from(std::iter::once("watsup").chain(args)).unwrap();
+            match cli.command {
+                Some(Command::Export { format, .. }) => assert_eq!(format.get(), ExportFormat::Ics),
+                command => panic!("Expected export, got {:?}", command),
+            }
+        }
+        assert!(Cli::try_parse_from(["watsup", "export"]).is_err());
+        assert!(Cli::try_parse_from(["watsup", "export", "ics", "--format", "csv"]).is_err());
+    }
+
+    #[test]
+    fn test_export_to_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("frames.ics");
+        let store = InMemoryStore::new();
+        store
+            .insert_frame(&make_completed_frame("project", 9))
+            .unwrap();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        executor
+            .execute_command(&Command::Export {
+                format: ExportFormatArg {
+                    format: Some(ExportFormat::Ics),
+                    format_option: None,
+                },
+                filter: FrameFilter::default(),
+                output: Some(output.clone()),
+            })
+            .unwrap();
+
+        let content = std::fs::read_to_string(output).unwrap();
+        assert_eq!(content.matches("BEGIN:VEVENT").count(), 1);
+    }
+
+    #[test]
+    fn test_check_without_frames() {
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        executor
+            .execute_command(&Command::Check { fix: false })
+            .unwrap();
+
+        assert!(store.get_all_frames().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_with_overlapping_frames_changes_nothing() {
+        let store = InMemoryStore::new();
+        let first = make_completed_frame("first", 9);
+        let second = make_completed_frame("second", 9);
+        store.insert_frame(&first).unwrap();
+        store.insert_frame(&second).unwrap();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        executor
+            .execute_command(&Command::Check { fix: false })
+            .unwrap();
+
+        // Without --fix the overlap is only reported
+        for frame in [&first, &second] {
+            let stored = store.get_frame(frame.frame().id()).unwrap().unwrap();
+            assert_eq!(stored.frame().start(), frame.frame().start());
+            assert_eq!(stored.end(), frame.end());
+        }
+        assert_eq!(store.get_all_frames().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_check_fix_without_overlaps_changes_nothing() {
+        let store = InMemoryStore::new();
+        let frame = make_completed_frame("project", 9);
+        store.insert_frame(&frame).unwrap();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        executor
+            .execute_command(&Command::Check { fix: true })
+            .unwrap();
+
+        let stored = store.get_all_frames().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].frame().start(), frame.frame().start());
+        assert_eq!(stored[0].end(), frame.end());
+    }
+
+    #[test]
+    fn test_strip_edit_comments() {
+        let content = "# Error: invalid date\n  # indented comment\n{\n  \"project\": \"p\"\n}";
+        assert_eq!(strip_edit_comments(content), "{\n  \"project\": \"p\"\n}");
+    }
+
     #[test]
     fn test_start_project() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         let command = Command::Start {
-            project: "test_project".to_string(),
+            project: Some("test_project".to_string()),
             tags: vec![],
             no_gap: false,
         };
@@ -422,13 +2556,45 @@ mod tests {
         assert!(store.has_ongoing_frame());
     }
 
+    #[test]
+    fn test_start_resolves_alias() {
+        let store = InMemoryStore::new();
+        let config = Config::default().with_alias("wd", "world-domination");
+        let mut executor = CommandExecutor::new(&store, config);
+
+        executor
+            .execute_command(&Command::Start {
+                project: Some("wd/plans".to_string()),
+                tags: vec![],
+                no_gap: false,
+            })
+            .unwrap();
+
+        let ongoing = store.get().unwrap().unwrap();
+        assert_eq!(ongoing.project().as_str(), "world-domination/plans");
+    }
+
+    #[test]
+    fn test_start_project_with_empty_level_returns_error() {
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        let result = executor.execute_command(&Command::Start {
+            project: Some("client//area".to_string()),
+            tags: vec![],
+            no_gap: false,
+        });
+
+        assert!(matches!(result, Err(CliError::InvalidProjectName)));
+    }
+
     #[test]
     fn test_start_project_twice_returns_error() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         let command = Command::Start {
-            project: "test_project".to_string(),
+            project: Some("test_project".to_string()),
             tags: vec![],
             no_gap: false,
         };
@@ -446,7 +2612,7 @@ mod tests {
     #[test]
     fn test_stop_without_start_returns_error() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         let command = Command::Stop { at: None };
 
@@ -461,10 +2627,10 @@ mod tests {
     #[test]
     fn test_start_and_stop_project() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         let start_command = Command::Start {
-            project: "test project".to_string(),
+            project: Some("test project".to_string()),
             tags: vec![],
             no_gap: false,
         };
@@ -482,7 +2648,7 @@ mod tests {
     #[test]
     fn test_cancel_without_start_returns_error() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         let command = Command::Cancel;
 
@@ -497,10 +2663,10 @@ mod tests {
     #[test]
     fn test_start_and_cancel_project() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         let start_command = Command::Start {
-            project: "test project".to_string(),
+            project: Some("test project".to_string()),
             tags: vec![],
             no_gap: false,
         };
@@ -515,12 +2681,72 @@ mod tests {
         assert!(!store.has_ongoing_frame())
     }
 
+    #[test]
+    fn test_pause_and_resume_project() {
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        assert!(matches!(
+            executor.execute_command(&Command::Pause),
+            Err(CliError::NoOngoingRecording)
+        ));
+        executor
+            .execute_command(&Command::Start {
+                project: Some("test project".to_string()),
+                tags: vec![],
+                no_gap: false,
+            })
+            .unwrap();
+        assert!(matches!(
+            executor.execute_command(&Command::Resume),
+            Err(CliError::NotPaused)
+        ));
+
+        executor.execute_command(&Command::Pause).unwrap();
+        assert!(matches!(
+            executor.execute_command(&Command::Pause),
+            Err(CliError::AlreadyPaused)
+        ));
+        assert!(matches!(
+            get_state_store(&store).unwrap(),
+            StateStoreVariant::Paused(_)
+        ));
+
+        executor.execute_command(&Command::Resume).unwrap();
+        assert!(matches!(
+            get_state_store(&store).unwrap(),
+            StateStoreVariant::Ongoing(_)
+        ));
+        executor
+            .execute_command(&Command::Stop { at: None })
+            .unwrap();
+        assert!(!store.has_ongoing_frame());
+    }
+
+    #[test]
+    fn test_parse_idle_gap_answer() {
+        use chrono::TimeZone;
+        let last_end = Local.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+        let now = Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(parse_idle_gap_answer("", last_end, now), Ok(now));
+        assert_eq!(parse_idle_gap_answer("n", last_end, now), Ok(now));
+        assert_eq!(parse_idle_gap_answer("e", last_end, now), Ok(last_end));
+        assert_eq!(
+            parse_idle_gap_answer("10:30", last_end, now),
+            Ok(Local.with_ymd_and_hms(2025, 1, 1, 10, 30, 0).unwrap())
+        );
+        assert!(parse_idle_gap_answer("08:00", last_end, now).is_err());
+        assert!(parse_idle_gap_answer("13:00", last_end, now).is_err());
+        assert!(parse_idle_gap_answer("later", last_end, now).is_err());
+    }
+
     #[test]
     fn test_list_projects_empty() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
-        let command = Command::Projects;
+        let command = Command::Projects { tree: false };
         let result = executor.execute_command(&command);
 
         assert!(result.is_ok());
@@ -529,10 +2755,10 @@ mod tests {
     #[test]
     fn test_stop_with_future_date_returns_error() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         let start_command = Command::Start {
-            project: "test project".to_string(),
+            project: Some("test project".to_string()),
             tags: vec![],
             no_gap: false,
         };
@@ -552,13 +2778,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_stop_before_tracked_time_keeps_frame() {
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+        let start = Local::now() - Duration::hours(2);
+        store
+            .store(&OngoingFrame::with_pauses(
+                NonEmptyString::new("project").unwrap().into(),
+                start,
+                vec![],
+                vec![crate::state::Pause {
+                    start,
+                    end: Some(start + Duration::hours(1)),
+                }],
+            ))
+            .unwrap();
+
+        // Before the start and within the first pause
+        for at in [start - Duration::minutes(10), start + Duration::minutes(30)] {
+            let result = executor.execute_command(&Command::Stop { at: Some(at) });
+            assert!(matches!(result, Err(CliError::StopBeforeTrackedTime(_))));
+            assert!(store.get().unwrap().is_some());
+            assert!(store.get_all_frames().unwrap().is_empty());
+        }
+
+        executor
+            .execute_command(&Command::Stop {
+                at: Some(start + Duration::minutes(90)),
+            })
+            .unwrap();
+        assert!(store.get().unwrap().is_none());
+        assert_eq!(store.get_all_frames().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_start_with_tags() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         let command = Command::Start {
-            project: "test project".to_string(),
+            project: Some("test project".to_string()),
             tags: vec!["tag1".to_string(), "tag2".to_string()],
             no_gap: false,
         };
@@ -570,11 +2830,11 @@ mod tests {
     #[test]
     fn test_start_with_no_gap() {
         let store = InMemoryStore::new();
-        let mut executor = CommandExecutor::new(&store);
+        let mut executor = CommandExecutor::new(&store, Config::default());
 
         // First, create and stop a frame
         let start1 = Command::Start {
-            project: "project1".to_string(),
+            project: Some("project1".to_string()),
             tags: vec![],
             no_gap: false,
         };
@@ -585,7 +2845,7 @@ mod tests {
 
         // Now start a new frame with no_gap
         let start2 = Command::Start {
-            project: "project2".to_string(),
+            project: Some("project2".to_string()),
             tags: vec![],
             no_gap: true,
         };
@@ -594,4 +2854,110 @@ mod tests {
         assert!(result.is_ok());
         assert!(store.has_ongoing_frame());
     }
+
+    #[test]
+    fn test_restart_last_frame() {
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+        executor
+            .execute_command(&Command::Start {
+                project: Some("project".to_string()),
+                tags: vec!["tag".to_string()],
+                no_gap: false,
+            })
+            .unwrap();
+        executor
+            .execute_command(&Command::Stop { at: None })
+            .unwrap();
+
+        executor
+            .execute_command(&Command::Restart { id: None })
+            .unwrap();
+
+        let ongoing = store.get().unwrap().unwrap();
+        assert_eq!(ongoing.project().as_str(), "project");
+        assert_eq!(ongoing.tags()[0].as_str(), "tag");
+        assert!(matches!(
+            executor.execute_command(&Command::Restart { id: None }),
+            Err(CliError::OngoingProject(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_pre_hook_rejects_command() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let data_dir = tempfile::tempdir().unwrap();
+        let hook = hooks::hooks_dir(data_dir.path()).join("pre-start");
+        std::fs::create_dir_all(hook.parent().unwrap()).unwrap();
+        std::fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::new(data_dir.path().into()));
+
+        let result = executor.execute_command(&Command::Start {
+            project: Some("project".to_string()),
+            tags: vec![],
+            no_gap: false,
+        });
+
+        assert!(matches!(result, Err(CliError::HookRejected(_))));
+        assert!(store.get().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_restart_project_without_colors() {
+        let store = InMemoryStore::new();
+        store
+            .insert_frame(&make_completed_frame("client/area", 9))
+            .unwrap();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        // Colors are on when restarting from a terminal
+        colored::control::set_override(true);
+        let result = executor.execute_command(&Command::Restart { id: None });
+        colored::control::unset_override();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            store.get().unwrap().unwrap().project().as_str(),
+            "client/area"
+        );
+    }
+
+    #[test]
+    fn test_remove_frame() {
+        let store = InMemoryStore::new();
+        let frame = make_completed_frame("project", 9);
+        store.insert_frame(&frame).unwrap();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        executor
+            .execute_command(&Command::Remove {
+                id: frame.frame().id().to_string(),
+                force: true,
+            })
+            .unwrap();
+
+        assert!(store.get_all_frames().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_status_quiet_when_idle() {
+        let store = InMemoryStore::new();
+        let mut executor = CommandExecutor::new(&store, Config::default());
+
+        let quiet = executor.execute_command(&Command::Status {
+            output: StatusOutput::default(),
+            quiet: true,
+        });
+        let not_quiet = executor.execute_command(&Command::Status {
+            output: StatusOutput::default(),
+            quiet: false,
+        });
+
+        assert!(quiet.is_ok());
+        assert!(matches!(not_quiet, Err(CliError::NoOngoingRecording)));
+    }
 }
@@ -79,7 +79,7 @@ mod tests {
     fn parse_full_datetime() {
         let datetime_str = "2025-01-02 11:12";
         let datetime = NaiveDateTime::new(
-            NaiveDate::from_ymd_opt(2025, 01, 02).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(),
             NaiveTime::from_hms_opt(11, 12, 00).unwrap(),
         );
         match parse_datetime_options(datetime_str) {
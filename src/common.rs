@@ -15,6 +15,10 @@ impl NonEmptyString {
             Some(Self(t.to_string()))
         }
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 impl PartialEq for NonEmptyString {
@@ -0,0 +1,168 @@
+// Shell completion scripts, completing project names, tags and frame ids from the stored frames
+//
+
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::{Shell, generate};
+
+use crate::cli::Cli;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// The values which the completion scripts ask for with `watsup __complete`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CompletionValues {
+    Projects,
+    Tags,
+    /// The ids of the most recent frames
+    Ids,
+}
+
+/// Completes the projects, tags and ids with `watsup __complete` and falls back to the generated
+/// `_watsup` for everything else
+const BASH_DYNAMIC_COMPLETION: &str = r#"
+_watsup_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}" values=""
+    if [[ "${cur}" != -* ]]; then
+        case "${prev}" in
+            -p|--project) values="projects" ;;
+            -T|--tag) values="tags" ;;
+            *)
+                case "${COMP_WORDS[1]}" in
+                    start)
+                        if [[ ${COMP_CWORD} -eq 2 ]]; then values="projects"; else values="tags"; fi ;;
+                    edit|restart|remove)
+                        if [[ ${COMP_CWORD} -eq 2 ]]; then values="ids"; fi ;;
+                esac ;;
+        esac
+    fi
+    if [[ -n "${values}" ]]; then
+        local IFS=$'\n'
+        COMPREPLY=( $(compgen -W "$(watsup __complete ${values} 2>/dev/null)" -- "${cur}") )
+        return 0
+    fi
+    _watsup "$@"
+}
+
+complete -F _watsup_dynamic -o bashdefault -o default watsup
+"#;
+
+/// Used by the generated `_watsup` in place of `_default` for the arguments in `ZSH_DYNAMIC_ARGS`
+const ZSH_DYNAMIC_COMPLETION: &str = r#"
+_watsup_projects() {
+    local -a values
+    values=("${(@f)$(watsup __complete projects 2>/dev/null)}")
+    compadd -a values
+}
+
+_watsup_tags() {
+    local -a values
+    values=("${(@f)$(watsup __complete tags 2>/dev/null)}")
+    compadd -a values
+}
+
+_watsup_ids() {
+    local -a values
+    values=("${(@f)$(watsup __complete ids 2>/dev/null)}")
+    compadd -a values
+}
+"#;
+
+/// Arguments of the generated zsh script which are completed by the functions in
+/// `ZSH_DYNAMIC_COMPLETION`, identified by the start of their specification or their value name
+const ZSH_DYNAMIC_ARGS: [(&str, &str); 7] = [
+    ("'::project -- ", "_watsup_projects"),
+    ("]:PROJECT:", "_watsup_projects"),
+    ("]:PROJECTS:", "_watsup_projects"),
+    ("'*::tags -- ", "_watsup_tags"),
+    ("]:TAGS:", "_watsup_tags"),
+    ("'::id -- ", "_watsup_ids"),
+    ("':id -- ", "_watsup_ids"),
+];
+
+const FISH_DYNAMIC_COMPLETION: &str = r#"
+complete -c watsup -n "__fish_watsup_using_subcommand start; and test (count (commandline -opc)) -eq 2" -f -a "(watsup __complete projects)"
+complete -c watsup -n "__fish_watsup_using_subcommand start; and test (count (commandline -opc)) -gt 2" -f -a "(watsup __complete tags)"
+complete -c watsup -n "__fish_watsup_using_subcommand edit restart remove" -f -a "(watsup __complete ids)"
+complete -c watsup -n "__fish_watsup_using_subcommand edit export" -s p -l project -r -f -a "(watsup __complete projects)"
+complete -c watsup -n "__fish_watsup_using_subcommand export" -s T -l tag -r -f -a "(watsup __complete tags)"
+"#;
+
+/// Replace the `_default` completion of the arguments in `ZSH_DYNAMIC_ARGS`. The specification
+/// of an argument with a multi-line help ends on a later line than it starts.
+fn add_zsh_dynamic_completion(script: &str) -> String {
+    let mut lines = Vec::new();
+    let mut function = None;
+    for line in script.lines() {
+        if let Some((_, f)) = ZSH_DYNAMIC_ARGS
+            .iter()
+            .find(|(pattern, _)| line.contains(pattern))
+        {
+            function = Some(*f);
+        }
+        match function {
+            Some(f) if line.ends_with(":_default' \\") => {
+                lines.push(line.replace(":_default' \\", &format!(":{}' \\", f)));
+                function = None;
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    // The helper functions have to be defined before the script calls `_watsup` at its end
+    let (compdef, rest) = lines.split_first().expect("Generated zsh script is empty");
+    format!(
+        "{}\n{}\n{}\n",
+        compdef,
+        ZSH_DYNAMIC_COMPLETION,
+        rest.join("\n")
+    )
+}
+
+/// The completion script for the shell. It is generated from the `Cli` definition and extended
+/// by the completion of projects, tags and frame ids for the shell.
+pub fn script(shell: CompletionShell) -> String {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    let mut generated = Vec::new();
+    let clap_shell = match shell {
+        CompletionShell::Bash => Shell::Bash,
+        CompletionShell::Zsh => Shell::Zsh,
+        CompletionShell::Fish => Shell::Fish,
+    };
+    generate(clap_shell, &mut command, name, &mut generated);
+    let generated = String::from_utf8_lossy(&generated);
+    match shell {
+        CompletionShell::Bash => format!("{}{}", generated, BASH_DYNAMIC_COMPLETION),
+        CompletionShell::Zsh => add_zsh_dynamic_completion(&generated),
+        CompletionShell::Fish => format!("{}{}", generated, FISH_DYNAMIC_COMPLETION),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zsh_completes_projects_tags_and_ids() {
+        let script = script(CompletionShell::Zsh);
+        assert!(script.starts_with("#compdef watsup\n"));
+        // start <project> [tags], edit --project, edit/restart [id], remove <id> and
+        // export --project/--tag
+        assert_eq!(script.matches(":_watsup_projects' \\").count(), 5);
+        assert_eq!(script.matches(":_watsup_tags' \\").count(), 3);
+        assert_eq!(script.matches(":_watsup_ids' \\").count(), 3);
+        assert!(script.find("_watsup_ids()") < script.find("compdef _watsup watsup"));
+    }
+
+    #[test]
+    fn test_bash_and_fish_complete_from_frames() {
+        let bash = script(CompletionShell::Bash);
+        assert!(bash.ends_with("complete -F _watsup_dynamic -o bashdefault -o default watsup\n"));
+        let fish = script(CompletionShell::Fish);
+        assert!(fish.contains("(watsup __complete ids)"));
+    }
+}
@@ -1,10 +1,113 @@
-use std::{env, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
+use chrono::Duration;
+use serde::Deserialize;
+
+use crate::{edit_format::EditFormat, project_tree::PROJECT_SEPARATOR};
+
+/// User settings read from the watsup config file. All settings are optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Settings {
+    edit_format: EditFormat,
+    /// Ask where to start a frame if the last frame ended more than this many minutes ago
+    idle_gap_minutes: Option<u32>,
+    backend: Option<BackendSettings>,
+    git: GitSettings,
+    /// Short names of projects, e.g. `wd = "world-domination"`
+    aliases: HashMap<String, String>,
+}
+
+/// Settings of the sync through a git repository in the watson directory, and of the git
+/// repositories in which work is tracked
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitSettings {
+    /// Commit the frames after each command which changes them
+    pub auto_commit: bool,
+    /// Tag started frames with the repository and branch of the current directory
+    pub auto_tag: bool,
+}
+
+/// The Crick server to sync the frames with
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackendSettings {
+    /// The URL of the API, e.g. `https://crick.io/api/`
+    pub url: String,
+    /// The API token of the user
+    pub token: String,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    IO(std::io::Error),
+    Parse(toml::de::Error),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::IO(e) => write!(f, "Failed to read config file: {}", e),
+            ConfigError::Parse(e) => write!(f, "Invalid config file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Clone)]
 pub struct Config {
     data_store: PathBuf,
+    edit_format: EditFormat,
+    idle_gap: Option<Duration>,
+    backend: Option<BackendSettings>,
+    git: GitSettings,
+    aliases: HashMap<String, String>,
+}
+
+/// The folder of watsup's own files, i.e. the config file and the log
+pub fn get_config_folder() -> PathBuf {
+    PathBuf::from(env::var("HOME").unwrap()).join(".config/watsup")
+}
+
+/// The Unix socket of the daemon. It is in `$XDG_RUNTIME_DIR` if set, as the directory belongs
+/// to the user only, and in the config folder otherwise.
+#[cfg(unix)]
+pub fn get_socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) if !runtime_dir.is_empty() => {
+            PathBuf::from(runtime_dir).join("watsup.sock")
+        }
+        _ => get_config_folder().join("watsup.sock"),
+    }
 }
 
 impl Config {
+    /// Load the config, applying the user settings from `config.toml` in the config folder if
+    /// the file exists.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        let settings_path = get_config_folder().join("config.toml");
+        if settings_path.exists() {
+            let content = std::fs::read_to_string(settings_path).map_err(ConfigError::IO)?;
+            let settings: Settings = toml::from_str(&content).map_err(ConfigError::Parse)?;
+            config.edit_format = settings.edit_format;
+            config.idle_gap = settings
+                .idle_gap_minutes
+                .map(|minutes| Duration::minutes(minutes.into()));
+            config.backend = settings.backend;
+            config.git = settings.git;
+            config.aliases = settings.aliases;
+        }
+        Ok(config)
+    }
+
     pub fn get_state_path(&self) -> PathBuf {
         self.data_store.join("state")
     }
@@ -12,6 +115,47 @@ impl Config {
     pub fn get_frames_path(&self) -> PathBuf {
         self.data_store.join("frames")
     }
+
+    /// The watson directory, which contains all data files
+    pub fn get_data_dir(&self) -> &Path {
+        &self.data_store
+    }
+
+    /// The file in which watson stores the time of the last sync
+    pub fn get_last_sync_path(&self) -> PathBuf {
+        self.data_store.join("last_sync")
+    }
+
+    pub fn get_edit_format(&self) -> EditFormat {
+        self.edit_format
+    }
+
+    /// The gap since the last frame after which `start` asks where to start. None if disabled.
+    pub fn get_idle_gap(&self) -> Option<Duration> {
+        self.idle_gap
+    }
+
+    pub fn get_backend(&self) -> Option<&BackendSettings> {
+        self.backend.as_ref()
+    }
+
+    pub fn get_git(&self) -> &GitSettings {
+        &self.git
+    }
+
+    /// The project an alias stands for. The alias may also be the top level of a hierarchical
+    /// project, e.g. `wd/plans` for `world-domination/plans`. Other names are returned as given.
+    pub fn resolve_alias(&self, project: &str) -> String {
+        let (top_level, rest) = match project.split_once(PROJECT_SEPARATOR) {
+            Some((top_level, rest)) => (top_level, Some(rest)),
+            None => (project, None),
+        };
+        match (self.aliases.get(top_level), rest) {
+            (Some(resolved), Some(rest)) => format!("{}{}{}", resolved, PROJECT_SEPARATOR, rest),
+            (Some(resolved), None) => resolved.clone(),
+            (None, _) => project.to_string(),
+        }
+    }
 }
 
 impl Default for Config {
@@ -23,6 +167,11 @@ impl Default for Config {
                 "linux" => home.join(".config/watson"),
                 _ => "/tmp/".into(),
             },
+            edit_format: EditFormat::default(),
+            idle_gap: None,
+            backend: None,
+            git: GitSettings::default(),
+            aliases: HashMap::new(),
         }
     }
 }
@@ -32,6 +181,75 @@ impl Config {
     pub fn new(storage_path: PathBuf) -> Self {
         Self {
             data_store: storage_path,
+            edit_format: EditFormat::default(),
+            idle_gap: None,
+            backend: None,
+            git: GitSettings::default(),
+            aliases: HashMap::new(),
         }
     }
+
+    pub fn with_alias(mut self, alias: &str, project: &str) -> Self {
+        self.aliases.insert(alias.to_string(), project.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_default_when_empty() {
+        let settings: Settings = toml::from_str("").unwrap();
+        assert_eq!(settings.edit_format, EditFormat::Json);
+    }
+
+    #[test]
+    fn test_settings_edit_format() {
+        let settings: Settings = toml::from_str("edit_format = \"yaml\"").unwrap();
+        assert_eq!(settings.edit_format, EditFormat::Yaml);
+    }
+
+    #[test]
+    fn test_settings_backend() {
+        let settings: Settings =
+            toml::from_str("[backend]\nurl = \"http://localhost/api\"\ntoken = \"secret\"")
+                .unwrap();
+        let backend = settings.backend.unwrap();
+        assert_eq!(backend.url, "http://localhost/api");
+        assert_eq!(backend.token, "secret");
+    }
+
+    #[test]
+    fn test_settings_idle_gap() {
+        let settings: Settings = toml::from_str("").unwrap();
+        assert_eq!(settings.idle_gap_minutes, None);
+        let settings: Settings = toml::from_str("idle_gap_minutes = 30").unwrap();
+        assert_eq!(settings.idle_gap_minutes, Some(30));
+    }
+
+    #[test]
+    fn test_settings_git() {
+        let settings: Settings = toml::from_str("").unwrap();
+        assert!(!settings.git.auto_commit);
+        let settings: Settings = toml::from_str("[git]\nauto_commit = true").unwrap();
+        assert!(settings.git.auto_commit);
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        let settings: Settings = toml::from_str("[aliases]\nwd = \"world-domination\"").unwrap();
+        let mut config = Config::new(PathBuf::from("/tmp"));
+        config.aliases = settings.aliases;
+        assert_eq!(config.resolve_alias("wd"), "world-domination");
+        assert_eq!(config.resolve_alias("wd/plans"), "world-domination/plans");
+        assert_eq!(config.resolve_alias("wdx"), "wdx");
+        assert_eq!(config.resolve_alias("other/wd"), "other/wd");
+    }
+
+    #[test]
+    fn test_settings_unknown_edit_format_is_error() {
+        assert!(toml::from_str::<Settings>("edit_format = \"xml\"").is_err());
+    }
 }
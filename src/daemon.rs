@@ -0,0 +1,397 @@
+// The daemon of `watsup daemon`, which serves a JSON-RPC 2.0 API on a Unix socket, and the
+// client with which the CLI uses a running daemon
+//
+
+use std::{
+    fmt::Display,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::PermissionsExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::stores::watson;
+
+/// How long the daemon waits for the next request of a connection. The daemon serves one
+/// connection at a time, so an idle client must not block the others.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the client waits for the answer of the daemon before it gives up
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The error codes defined by JSON-RPC 2.0
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// The code of errors of the executed command, e.g. if no project is started
+pub const COMMAND_FAILED: i64 = -32000;
+
+#[derive(Debug)]
+pub enum DaemonError {
+    IO(io::Error),
+    /// Another daemon listens on the socket
+    AlreadyRunning(PathBuf),
+    /// The answer of the daemon could not be read
+    InvalidResponse(String),
+    /// The daemon answered with an error
+    Rpc(RpcError),
+}
+
+impl Display for DaemonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DaemonError::IO(e) => write!(f, "IO error: {}", e),
+            DaemonError::AlreadyRunning(socket) => {
+                write!(f, "A daemon is already running on {}", socket.display())
+            }
+            DaemonError::InvalidResponse(details) => {
+                write!(f, "Invalid response from the daemon: {}", details)
+            }
+            DaemonError::Rpc(error) => write!(f, "{}", error.message),
+        }
+    }
+}
+
+impl From<io::Error> for DaemonError {
+    fn from(error: io::Error) -> Self {
+        DaemonError::IO(error)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Response {
+    jsonrpc: String,
+    id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+impl Response {
+    fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StartParams {
+    pub project: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub no_gap: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StopParams {
+    #[serde(default)]
+    pub at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LogParams {
+    #[serde(default)]
+    pub current: bool,
+    #[serde(default)]
+    pub from: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Local>>,
+}
+
+/// The methods of the API, which mirror the commands with the same name
+#[derive(Debug)]
+pub enum DaemonRequest {
+    /// Returns the status of the started frame, like `status --json`
+    Start(StartParams),
+    /// Returns a `StopResult`
+    Stop(StopParams),
+    /// Returns the status like `status --json`, or `null` if no project is started
+    Status,
+    /// Returns the frames in the format of watson's frames file
+    Log(LogParams),
+}
+
+impl DaemonRequest {
+    fn parse(method: &str, params: Value) -> Result<Self, RpcError> {
+        // Methods without parameters may be called without `params`
+        let params = match params {
+            Value::Null => Value::Object(Default::default()),
+            params => params,
+        };
+        fn parse_params<P: DeserializeOwned>(params: Value) -> Result<P, RpcError> {
+            serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+        }
+        match method {
+            "start" => Ok(DaemonRequest::Start(parse_params(params)?)),
+            "stop" => Ok(DaemonRequest::Stop(parse_params(params)?)),
+            "status" => Ok(DaemonRequest::Status),
+            "log" => Ok(DaemonRequest::Log(parse_params(params)?)),
+            method => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method {}", method),
+            )),
+        }
+    }
+
+    fn method_and_params(&self) -> (&'static str, Value) {
+        let params = match self {
+            DaemonRequest::Start(params) => serde_json::to_value(params),
+            DaemonRequest::Stop(params) => serde_json::to_value(params),
+            DaemonRequest::Status => Ok(Value::Object(Default::default())),
+            DaemonRequest::Log(params) => serde_json::to_value(params),
+        }
+        .expect("Parameters are serializable");
+        let method = match self {
+            DaemonRequest::Start(_) => "start",
+            DaemonRequest::Stop(_) => "stop",
+            DaemonRequest::Status => "status",
+            DaemonRequest::Log(_) => "log",
+        };
+        (method, params)
+    }
+}
+
+/// The result of the `stop` method
+#[derive(Serialize, Deserialize)]
+pub struct StopResult {
+    pub project: String,
+    pub start: DateTime<Local>,
+    /// The saved frames, one per segment between the pauses
+    pub frames: Vec<watson::Frame>,
+    /// The saved frames paired with the stored frames they overlap
+    #[serde(default)]
+    pub overlaps: Vec<(watson::Frame, watson::Frame)>,
+}
+
+/// Answer one request, given as line of JSON
+fn respond(
+    line: &str,
+    execute: &mut impl FnMut(DaemonRequest) -> Result<Value, RpcError>,
+) -> Response {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Response::new(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())));
+        }
+    };
+    log::debug!(
+        "Daemon request. method={} id={}",
+        request.method,
+        request.id
+    );
+    let result = DaemonRequest::parse(&request.method, request.params).and_then(&mut *execute);
+    Response::new(request.id, result)
+}
+
+fn serve_connection(
+    stream: UnixStream,
+    execute: &mut impl FnMut(DaemonRequest) -> Result<Value, RpcError>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = respond(&line, execute);
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Listen on the socket and answer the requests with `execute`, one request at a time. A socket
+/// left behind by a daemon which did not shut down cleanly is replaced.
+pub fn serve(
+    socket: &Path,
+    mut execute: impl FnMut(DaemonRequest) -> Result<Value, RpcError>,
+) -> Result<(), DaemonError> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            return Err(DaemonError::AlreadyRunning(socket.to_path_buf()));
+        }
+        std::fs::remove_file(socket)?;
+    }
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+    println!("Listening on {}", socket.display());
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| serve_connection(stream, &mut execute));
+        if let Err(e) = result {
+            log::warn!("Daemon connection failed. error={:?}", e);
+        }
+    }
+    Ok(())
+}
+
+/// A connection to a running daemon
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    next_id: u64,
+}
+
+impl Client {
+    /// Connect to the daemon. Returns `None` if no daemon is running.
+    pub fn connect(socket: &Path) -> Option<Self> {
+        let stream = UnixStream::connect(socket).ok()?;
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT)).ok()?;
+        Some(Self {
+            reader: BufReader::new(stream.try_clone().ok()?),
+            writer: stream,
+            next_id: 1,
+        })
+    }
+
+    pub fn call<R: DeserializeOwned>(&mut self, request: &DaemonRequest) -> Result<R, DaemonError> {
+        let (method, params) = request.method_and_params();
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = Request {
+            jsonrpc: "2.0".to_string(),
+            id: id.into(),
+            method: method.to_string(),
+            params,
+        };
+        let mut line = serde_json::to_string(&request)
+            .map_err(|e| DaemonError::InvalidResponse(e.to_string()))?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+
+        let mut line = String::new();
+        self.reader.read_line(&mut line)?;
+        let response: Response =
+            serde_json::from_str(&line).map_err(|e| DaemonError::InvalidResponse(e.to_string()))?;
+        if let Some(error) = response.error {
+            return Err(DaemonError::Rpc(error));
+        }
+        serde_json::from_value(response.result.unwrap_or(Value::Null))
+            .map_err(|e| DaemonError::InvalidResponse(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execute(request: DaemonRequest) -> Result<Value, RpcError> {
+        match request {
+            DaemonRequest::Start(params) => Ok(serde_json::json!({"project": params.project})),
+            DaemonRequest::Status => Err(RpcError::new(COMMAND_FAILED, "No project started")),
+            _ => Ok(Value::Null),
+        }
+    }
+
+    #[test]
+    fn test_respond() {
+        let response = respond(
+            r#"{"jsonrpc": "2.0", "id": 7, "method": "start", "params": {"project": "p"}}"#,
+            &mut execute,
+        );
+        assert_eq!(response.id, 7);
+        assert_eq!(response.result.unwrap()["project"], "p");
+
+        let response = respond(
+            r#"{"jsonrpc": "2.0", "id": 8, "method": "status"}"#,
+            &mut execute,
+        );
+        assert_eq!(response.error.unwrap().code, COMMAND_FAILED);
+    }
+
+    #[test]
+    fn test_respond_to_invalid_requests() {
+        let response = respond("not json", &mut execute);
+        assert_eq!(response.error.unwrap().code, PARSE_ERROR);
+        let response = respond(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "edit"}"#,
+            &mut execute,
+        );
+        assert_eq!(response.error.unwrap().code, METHOD_NOT_FOUND);
+        let response = respond(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "start", "params": {"tags": []}}"#,
+            &mut execute,
+        );
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_client_and_server() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let socket = dir.path().join("watsup.sock");
+        let server_socket = socket.clone();
+        std::thread::spawn(move || serve(&server_socket, execute));
+        let mut client = loop {
+            if let Some(client) = Client::connect(&socket) {
+                break client;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        let started: Value = client
+            .call(&DaemonRequest::Start(StartParams {
+                project: "project".to_string(),
+                tags: Vec::new(),
+                no_gap: false,
+            }))
+            .unwrap();
+        assert_eq!(started["project"], "project");
+        assert!(matches!(
+            client.call::<Value>(&DaemonRequest::Status),
+            Err(DaemonError::Rpc(RpcError {
+                code: COMMAND_FAILED,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            serve(&socket, execute),
+            Err(DaemonError::AlreadyRunning(_))
+        ));
+    }
+}
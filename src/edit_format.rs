@@ -0,0 +1,73 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// The formats in which frames can be presented in the editor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EditFormat {
+    /// JSON, the same as watson uses
+    #[default]
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl EditFormat {
+    /// The file extension of the format, so that editors enable the matching syntax highlighting
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EditFormat::Json => ".json",
+            EditFormat::Toml => ".toml",
+            EditFormat::Yaml => ".yaml",
+        }
+    }
+
+    pub fn serialize<D: Serialize>(&self, document: &D) -> Result<String, String> {
+        match self {
+            EditFormat::Json => serde_json::to_string_pretty(document).map_err(|e| e.to_string()),
+            EditFormat::Toml => toml::to_string_pretty(document).map_err(|e| e.to_string()),
+            EditFormat::Yaml => serde_yaml_ng::to_string(document).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn deserialize<D: DeserializeOwned>(&self, content: &str) -> Result<D, String> {
+        match self {
+            EditFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            EditFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            EditFormat::Yaml => serde_yaml_ng::from_str(content).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Document {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+        list: Vec<String>,
+    }
+
+    #[test]
+    fn test_roundtrip_all_formats() {
+        let document = Document {
+            name: "name".to_string(),
+            value: None,
+            list: vec!["a".to_string(), "b".to_string()],
+        };
+        for format in EditFormat::value_variants() {
+            let serialized = format.serialize(&document).unwrap();
+            let deserialized: Document = format.deserialize(&serialized).unwrap();
+            assert_eq!(document, deserialized, "format={:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_error_is_returned() {
+        let result: Result<Document, String> = EditFormat::Toml.deserialize("name = ");
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,308 @@
+// Export of frames into formats of other tools
+//
+
+use std::fmt::Display;
+
+use chrono::{DateTime, Local, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{frame::CompletedFrame, stores::watson};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    /// iCalendar, with one event per frame
+    Ics,
+    /// The frames file of watson, which can be merged into another watson directory
+    WatsonJson,
+    /// The JSON format of `timew export`. The project is the first tag.
+    Timewarrior,
+    /// CSV with the same columns as `watson log --csv`
+    Csv,
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Json(serde_json::Error),
+    Csv(csv::Error),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Json(e) => write!(f, "JSON serialization failed: {}", e),
+            ExportError::Csv(e) => write!(f, "CSV serialization failed: {}", e),
+        }
+    }
+}
+
+/// Export the frames in the given format
+pub fn export(format: ExportFormat, frames: &[CompletedFrame]) -> Result<String, ExportError> {
+    match format {
+        ExportFormat::Ics => Ok(to_ics(
+            frames,
+            iana_time_zone::get_timezone().ok().as_deref(),
+        )),
+        ExportFormat::WatsonJson => to_watson_json(frames),
+        ExportFormat::Timewarrior => to_timewarrior(frames),
+        ExportFormat::Csv => to_csv(frames),
+    }
+}
+
+fn to_watson_json(frames: &[CompletedFrame]) -> Result<String, ExportError> {
+    let frames: Vec<watson::Frame> = frames.iter().map(watson::Frame::from).collect();
+    serde_json::to_string_pretty(&frames).map_err(ExportError::Json)
+}
+
+#[derive(Serialize)]
+struct TimewarriorInterval {
+    id: usize,
+    start: String,
+    end: String,
+    tags: Vec<String>,
+}
+
+const TIMEWARRIOR_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+fn to_timewarrior(frames: &[CompletedFrame]) -> Result<String, ExportError> {
+    let intervals: Vec<TimewarriorInterval> = frames
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| TimewarriorInterval {
+            // Like in timewarrior, the most recent interval has id 1
+            id: frames.len() - index,
+            start: frame
+                .frame()
+                .start()
+                .with_timezone(&Utc)
+                .format(TIMEWARRIOR_DATETIME_FORMAT)
+                .to_string(),
+            end: frame
+                .end()
+                .with_timezone(&Utc)
+                .format(TIMEWARRIOR_DATETIME_FORMAT)
+                .to_string(),
+            tags: std::iter::once(frame.frame().project().as_str())
+                .chain(frame.frame().tags().iter().map(|tag| tag.as_str()))
+                .map(String::from)
+                .collect(),
+        })
+        .collect();
+    serde_json::to_string_pretty(&intervals).map_err(ExportError::Json)
+}
+
+const CSV_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn to_csv(frames: &[CompletedFrame]) -> Result<String, ExportError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["id", "start", "stop", "project", "tags"])
+        .map_err(ExportError::Csv)?;
+    for frame in frames {
+        let tags: Vec<&str> = frame.frame().tags().iter().map(|t| t.as_str()).collect();
+        writer
+            .write_record([
+                frame.frame().id(),
+                &frame
+                    .frame()
+                    .start()
+                    .format(CSV_DATETIME_FORMAT)
+                    .to_string(),
+                &frame.end().format(CSV_DATETIME_FORMAT).to_string(),
+                frame.frame().project().as_str(),
+                &tags.join(", "),
+            ])
+            .map_err(ExportError::Csv)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| ExportError::Csv(e.into_error().into()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+const ICS_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// Lines of an iCalendar file should not be longer than this many bytes, see RFC 5545 3.1
+const ICS_MAX_LINE_LENGTH: usize = 75;
+
+fn format_ics_datetime(datetime: &DateTime<Local>) -> String {
+    datetime
+        .with_timezone(&Utc)
+        .format(ICS_DATETIME_FORMAT)
+        .to_string()
+}
+
+/// Escape a value of a TEXT property, see RFC 5545 3.3.11
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line into lines of at most `ICS_MAX_LINE_LENGTH` bytes, see RFC 5545 3.1
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+    for c in line.chars() {
+        if line_length + c.len_utf8() > ICS_MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            // The leading space of the continuation line counts towards its length
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Render the frames as iCalendar with one VEVENT per frame.
+///
+/// The frame id is used as UID, so calendars update an event instead of duplicating it when the
+/// frames are exported again. Times are written in UTC. The local timezone is given as calendar
+/// wide hint (`X-WR-TIMEZONE`), which calendar apps use to display the events.
+fn to_ics(frames: &[CompletedFrame], timezone: Option<&str>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//watsup//watsup {}//EN", env!("CARGO_PKG_VERSION")),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:watsup".to_string(),
+    ];
+    if let Some(timezone) = timezone {
+        lines.push(format!("X-WR-TIMEZONE:{}", timezone));
+    }
+    for frame in frames {
+        let last_edit = format_ics_datetime(&frame.frame().last_edit());
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", escape_ics_text(frame.frame().id())));
+        lines.push(format!("DTSTAMP:{}", last_edit));
+        lines.push(format!("LAST-MODIFIED:{}", last_edit));
+        lines.push(format!(
+            "DTSTART:{}",
+            format_ics_datetime(frame.frame().start())
+        ));
+        lines.push(format!("DTEND:{}", format_ics_datetime(&frame.end())));
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_ics_text(frame.frame().project().as_str())
+        ));
+        if !frame.frame().tags().is_empty() {
+            let categories: Vec<String> = frame
+                .frame()
+                .tags()
+                .iter()
+                .map(|tag| escape_ics_text(tag.as_str()))
+                .collect();
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_ics_line(line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{common::NonEmptyString, frame::Frame};
+
+    fn create_test_frame(project: &str, tags: &[&str]) -> CompletedFrame {
+        let start = Utc
+            .with_ymd_and_hms(2025, 1, 1, 9, 0, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let end = Utc
+            .with_ymd_and_hms(2025, 1, 1, 10, 30, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let frame = Frame::new(
+            NonEmptyString::new(project).unwrap().into(),
+            Some("0123456789abcdef0123456789abcdef".to_string()),
+            Some(start),
+            Some(end),
+            tags.iter()
+                .map(|tag| NonEmptyString::new(tag).unwrap())
+                .collect(),
+            Some(end),
+        );
+        CompletedFrame::from_frame(frame).unwrap()
+    }
+
+    #[test]
+    fn test_ics_event() {
+        let frames = vec![create_test_frame("world-domination", &["plan", "a,b"])];
+        let ics = to_ics(&frames, Some("Europe/Zurich"));
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert!(lines.contains(&"X-WR-TIMEZONE:Europe/Zurich"));
+        assert!(lines.contains(&"UID:0123456789abcdef0123456789abcdef"));
+        assert!(lines.contains(&"DTSTART:20250101T090000Z"));
+        assert!(lines.contains(&"DTEND:20250101T103000Z"));
+        assert!(lines.contains(&"SUMMARY:world-domination"));
+        assert!(lines.contains(&"CATEGORIES:plan,a\\,b"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_watson_json_can_be_read_by_watson_store() {
+        let frames = vec![create_test_frame("project", &["tag"])];
+        let json = to_watson_json(&frames).unwrap();
+
+        let parsed: Vec<watson::Frame> = serde_json::from_str(&json).unwrap();
+        let parsed: Vec<CompletedFrame> = parsed.into_iter().map(CompletedFrame::from).collect();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].frame().id(), frames[0].frame().id());
+        assert_eq!(parsed[0].frame().start(), frames[0].frame().start());
+        assert_eq!(parsed[0].end(), frames[0].end());
+    }
+
+    #[test]
+    fn test_timewarrior_roundtrip_with_import() {
+        let frames = vec![create_test_frame("project", &["tag"])];
+        let json = to_timewarrior(&frames).unwrap();
+
+        let import =
+            crate::import::parse(crate::import::ImportFormat::Timewarrior, &json, None).unwrap();
+
+        assert_eq!(import.frames.len(), 1);
+        assert_eq!(import.frames[0].frame().project().as_str(), "project");
+        assert_eq!(import.frames[0].frame().tags()[0].as_str(), "tag");
+        assert_eq!(import.frames[0].frame().start(), frames[0].frame().start());
+        assert_eq!(import.frames[0].end(), frames[0].end());
+    }
+
+    #[test]
+    fn test_csv() {
+        let frames = vec![create_test_frame("project", &["a", "b"])];
+        let csv = to_csv(&frames).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "id,start,stop,project,tags");
+        assert!(lines[1].starts_with("0123456789abcdef0123456789abcdef,"));
+        assert!(lines[1].ends_with(",project,\"a, b\""));
+    }
+
+    #[test]
+    fn test_ics_without_frames_and_timezone() {
+        let ics = to_ics(&[], None);
+        assert!(!ics.contains("VEVENT"));
+        assert!(!ics.contains("X-WR-TIMEZONE"));
+    }
+
+    #[test]
+    fn test_fold_ics_line() {
+        let line = format!("SUMMARY:{}", "x".repeat(100));
+        let folded = fold_ics_line(&line);
+        let lines: Vec<&str> = folded.trim_end().split("\r\n").collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), ICS_MAX_LINE_LENGTH);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+}
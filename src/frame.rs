@@ -1,31 +1,49 @@
-use std::{
-    fmt::Display,
-    hash::{DefaultHasher, Hash, Hasher},
-};
+use std::{fmt::Display, hash::Hash};
 
 use chrono::{DateTime, Duration, Local, NaiveDateTime};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
-use crate::{common::NonEmptyString, state::OngoingFrame};
+use crate::{common::NonEmptyString, project_tree::PROJECT_SEPARATOR, state::OngoingFrame};
 
-/// Generate a unique ID for the frame using a hash of the current time
+/// Generate a random unique ID for the frame.
+/// Like watson, the ID is a UUID v4 formatted as 32 hex characters.
 fn generate_id() -> String {
-    let mut hasher = DefaultHasher::new();
-    hasher.write(chrono::Local::now().to_string().as_bytes());
-    format!("{:x}", hasher.finish())
+    uuid::Uuid::new_v4().simple().to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 /// Represents a project name
 pub struct ProjectName(NonEmptyString);
 
+#[cfg(test)]
 impl From<NonEmptyString> for ProjectName {
     fn from(string: NonEmptyString) -> Self {
         ProjectName(string)
     }
 }
 
+/// Whether the name is a valid project name: it is not empty and has no empty level, like
+/// `client//area` or `client/`
+pub fn is_valid_project_name(name: &str) -> bool {
+    name.split(PROJECT_SEPARATOR)
+        .all(|level| !level.trim().is_empty())
+}
+
+impl ProjectName {
+    /// The project name, or `None` if the name is not valid, see `is_valid_project_name`
+    pub fn new(name: &str) -> Option<Self> {
+        if !is_valid_project_name(name) {
+            return None;
+        }
+        NonEmptyString::new(name).map(ProjectName)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
 impl Display for ProjectName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0.to_string().magenta())
@@ -94,23 +112,12 @@ impl Frame {
         }
     }
 
-    pub fn from(state: OngoingFrame) -> Self {
-        Frame {
-            project: state.project().clone(),
-            id: generate_id(),
-            start: *state.start(),
-            end: None,
-            tags: state.tags().into(),
-            last_edit: chrono::Local::now(),
-        }
-    }
-
     pub fn set_end(mut self, end: chrono::DateTime<chrono::Local>) -> CompletedFrame {
         self.end = Some(end);
         CompletedFrame::from_frame(self).unwrap()
     }
 
-    pub fn update_from(&mut self, edit: FrameEdit) {
+    pub fn update_from(&mut self, edit: ValidFrameEdit) {
         self.project = edit.project().clone();
         self.start = edit.start();
         self.end = edit.stop();
@@ -164,6 +171,19 @@ impl CompletedFrame {
     pub fn duration(&self) -> Duration {
         self.end() - self.frame().start()
     }
+
+    /// Whether the time ranges of the two frames intersect.
+    /// Frames that only touch (one ends when the other starts) do not overlap.
+    pub fn overlaps(&self, other: &CompletedFrame) -> bool {
+        self.frame().start() < &other.end() && other.frame().start() < &self.end()
+    }
+
+    /// Change the start and end time of the frame. Updates the last edit time.
+    pub fn set_times(&mut self, start: DateTime<Local>, end: DateTime<Local>) {
+        self.0.start = start;
+        self.0.end = Some(end);
+        self.0.last_edit = chrono::Local::now();
+    }
 }
 
 impl Ord for CompletedFrame {
@@ -203,33 +223,173 @@ impl Display for CompletedFrame {
     }
 }
 
+#[derive(Debug, Clone)]
+/// The reasons why a frame reference can not be resolved by `find_frame`
+pub enum FrameReferenceError {
+    /// No frame matches the reference
+    NotFound(String),
+    /// The id prefix matches multiple frames, which are the candidates
+    Ambiguous(String, Vec<CompletedFrame>),
+    /// The reference is empty or an id prefix shorter than `MIN_ID_PREFIX_LENGTH`
+    TooShort(String),
+}
+
+/// The minimum length of an id prefix referencing a frame, like git's abbreviated hashes
+pub const MIN_ID_PREFIX_LENGTH: usize = 4;
+
+/// Find the frame referenced by `reference` in `frames`, which must be ordered by start time.
+///
+/// Like in git, a frame can be referenced by a unique prefix of its id of at least
+/// `MIN_ID_PREFIX_LENGTH` characters. Like in watson, a frame
+/// can also be referenced by its position from the end, `-1` being the last frame, `-2` the
+/// second to last and so on.
+pub fn find_frame<'a>(
+    frames: &'a [CompletedFrame],
+    reference: &str,
+) -> Result<&'a CompletedFrame, FrameReferenceError> {
+    let not_found = || FrameReferenceError::NotFound(reference.to_string());
+    if reference.is_empty() {
+        return Err(FrameReferenceError::TooShort(reference.to_string()));
+    }
+    if let Some(position) = reference.strip_prefix('-') {
+        let position: usize = position.parse().map_err(|_| not_found())?;
+        return frames
+            .len()
+            .checked_sub(position)
+            .filter(|_| position > 0)
+            .map(|index| &frames[index])
+            .ok_or_else(not_found);
+    }
+    if let Some(frame) = frames.iter().find(|f| f.frame().id() == reference) {
+        return Ok(frame);
+    }
+    if reference.chars().count() < MIN_ID_PREFIX_LENGTH {
+        return Err(FrameReferenceError::TooShort(reference.to_string()));
+    }
+    let candidates: Vec<&CompletedFrame> = frames
+        .iter()
+        .filter(|f| f.frame().id().starts_with(reference))
+        .collect();
+    match candidates[..] {
+        [] => Err(not_found()),
+        [frame] => Ok(frame),
+        _ => Err(FrameReferenceError::Ambiguous(
+            reference.to_string(),
+            candidates.into_iter().cloned().collect(),
+        )),
+    }
+}
+
 const EDIT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 /// Frame representation used for editing a frame
 pub struct FrameEdit {
-    project: ProjectName,
+    project: String,
     start: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     stop: Option<String>,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The reasons why a `FrameEdit` can be rejected
+pub enum FrameEditError {
+    EmptyProject,
+    /// A level of the project is empty, like in `client//area`
+    InvalidProject(String),
+    InvalidDateFormat(String),
+    MissingStop,
+    StartAfterStop,
+    StopInFuture,
+}
+
+impl Display for FrameEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameEditError::EmptyProject => write!(f, "Project must not be empty"),
+            FrameEditError::InvalidProject(project) => write!(
+                f,
+                "Invalid project \"{}\", the levels separated by / must not be empty",
+                project
+            ),
+            FrameEditError::InvalidDateFormat(date) => write!(
+                f,
+                "Invalid date \"{}\", expected format YYYY-MM-DD HH:MM:SS",
+                date
+            ),
+            FrameEditError::MissingStop => write!(f, "Stop date is required for completed frames"),
+            FrameEditError::StartAfterStop => write!(f, "Start date must be before stop date"),
+            FrameEditError::StopInFuture => write!(f, "Stop date cannot be in the future"),
+        }
+    }
+}
+
+/// A `FrameEdit` whose fields have been checked and parsed.
+/// Obtained through `FrameEdit::validate`.
+#[derive(Debug, Clone)]
+pub struct ValidFrameEdit {
+    project: ProjectName,
+    start: DateTime<Local>,
+    stop: Option<DateTime<Local>>,
     tags: Vec<NonEmptyString>,
 }
 
+/// The project of an edited or received frame, whose name is trimmed
+pub fn parse_project(project: &str) -> Result<ProjectName, FrameEditError> {
+    let project = project.trim();
+    if project.is_empty() {
+        return Err(FrameEditError::EmptyProject);
+    }
+    ProjectName::new(project).ok_or_else(|| FrameEditError::InvalidProject(project.to_string()))
+}
+
+fn parse_edit_datetime(date: &str) -> Result<DateTime<Local>, FrameEditError> {
+    NaiveDateTime::parse_from_str(date.trim(), EDIT_DATETIME_FORMAT)
+        .ok()
+        .and_then(|naive| naive.and_local_timezone(Local).earliest())
+        .ok_or(FrameEditError::InvalidDateFormat(date.to_string()))
+}
+
 impl FrameEdit {
+    /// Check the edit and parse it into a `ValidFrameEdit`.
+    ///
+    /// If `require_stop` is set, a missing stop date is an error. This is the case for completed frames.
+    pub fn validate(&self, require_stop: bool) -> Result<ValidFrameEdit, FrameEditError> {
+        let project = parse_project(&self.project)?;
+        let start = parse_edit_datetime(&self.start)?;
+        let stop = self.stop.as_deref().map(parse_edit_datetime).transpose()?;
+        match stop {
+            Some(stop) if start > stop => return Err(FrameEditError::StartAfterStop),
+            Some(stop) if stop > Local::now() => return Err(FrameEditError::StopInFuture),
+            None if require_stop => return Err(FrameEditError::MissingStop),
+            _ => {}
+        }
+        let tags = self
+            .tags
+            .iter()
+            .filter_map(|tag| NonEmptyString::new(tag.trim()))
+            .collect();
+        Ok(ValidFrameEdit {
+            project,
+            start,
+            stop,
+            tags,
+        })
+    }
+}
+
+impl ValidFrameEdit {
     pub fn project(&self) -> &ProjectName {
         &self.project
     }
 
     pub fn start(&self) -> DateTime<Local> {
-        let naive = NaiveDateTime::parse_from_str(&self.start, EDIT_DATETIME_FORMAT).unwrap();
-        naive.and_local_timezone(Local).single().unwrap()
+        self.start
     }
 
     pub fn stop(&self) -> Option<DateTime<Local>> {
         self.stop
-            .clone()
-            .map(|s| NaiveDateTime::parse_from_str(&s, EDIT_DATETIME_FORMAT).unwrap())
-            .map(|d| d.and_local_timezone(Local).unwrap())
     }
 
     pub fn tags(&self) -> &[NonEmptyString] {
@@ -240,12 +400,12 @@ impl FrameEdit {
 impl From<&Frame> for FrameEdit {
     fn from(frame: &Frame) -> Self {
         FrameEdit {
-            project: frame.project().clone(),
+            project: frame.project().0.to_string(),
             start: frame.start().format(EDIT_DATETIME_FORMAT).to_string(),
             stop: frame
                 .end()
-                .and_then(|e| Some(e.format(EDIT_DATETIME_FORMAT).to_string())),
-            tags: Vec::from(frame.tags()),
+                .map(|e| e.format(EDIT_DATETIME_FORMAT).to_string()),
+            tags: frame.tags().iter().map(|tag| tag.to_string()).collect(),
         }
     }
 }
@@ -253,13 +413,46 @@ impl From<&Frame> for FrameEdit {
 impl From<&OngoingFrame> for FrameEdit {
     fn from(ongoing_frame: &OngoingFrame) -> Self {
         FrameEdit {
-            project: ongoing_frame.project().clone(),
+            project: ongoing_frame.project().0.to_string(),
             start: ongoing_frame
                 .start()
                 .format(EDIT_DATETIME_FORMAT)
                 .to_string(),
             stop: None,
-            tags: Vec::from(ongoing_frame.tags()),
+            tags: ongoing_frame
+                .tags()
+                .iter()
+                .map(|tag| tag.to_string())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+/// Frame representation used for editing multiple frames at once.
+/// Entries without an id are frames which are added by the edit.
+pub struct BulkFrameEdit {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(flatten)]
+    edit: FrameEdit,
+}
+
+impl BulkFrameEdit {
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn edit(&self) -> &FrameEdit {
+        &self.edit
+    }
+}
+
+impl From<&Frame> for BulkFrameEdit {
+    fn from(frame: &Frame) -> Self {
+        BulkFrameEdit {
+            id: Some(frame.id().to_string()),
+            edit: FrameEdit::from(frame),
         }
     }
 }
@@ -273,12 +466,30 @@ impl PartialEq for ProjectName {
 impl Eq for ProjectName {}
 
 pub trait FrameStore {
-    type FrameStoreError;
+    type FrameStoreError: Display;
 
-    /// Save a frame to the store.
-    /// If the frame already exists (identified by "id") it will be updated, otherwise inserted.
-    /// Returns an error if the saving failed.
-    fn save_frame(&self, frame: &CompletedFrame) -> Result<(), Self::FrameStoreError>;
+    /// Insert a new frame into the store.
+    /// Returns an error if a frame with the same id already exists or the saving failed.
+    #[cfg(test)]
+    fn insert_frame(&self, frame: &CompletedFrame) -> Result<(), Self::FrameStoreError> {
+        self.update_frames(std::slice::from_ref(frame), &[], &[])
+    }
+
+    /// Update an existing frame in the store, identified by its id.
+    /// Returns an error if no frame with the id exists or the saving failed.
+    fn update_frame(&self, frame: &CompletedFrame) -> Result<(), Self::FrameStoreError> {
+        self.update_frames(&[], std::slice::from_ref(frame), &[])
+    }
+
+    /// Insert, update and delete multiple frames with a single write to the store.
+    /// Frames in `insert` and `update` are checked like in `insert_frame` and `update_frame`,
+    /// frames with an id in `delete` are removed. Nothing is written if any check fails.
+    fn update_frames(
+        &self,
+        insert: &[CompletedFrame],
+        update: &[CompletedFrame],
+        delete: &[String],
+    ) -> Result<(), Self::FrameStoreError>;
 
     /// Get all the projects of frames stored in this store.
     fn get_projects(&self) -> Result<Vec<ProjectName>, Self::FrameStoreError>;
@@ -290,6 +501,9 @@ pub trait FrameStore {
     /// Returns a CompletedFrame if one matching `frame_id` exists, otherwise None.
     fn get_frame(&self, frame_id: &str) -> Result<Option<CompletedFrame>, Self::FrameStoreError>;
 
+    /// Get all frames in the store, ordered by start time
+    fn get_all_frames(&self) -> Result<Vec<CompletedFrame>, Self::FrameStoreError>;
+
     /// Get all frames that fall between start and end time
     fn get_frames(
         &self,
@@ -297,3 +511,198 @@ pub trait FrameStore {
         end: DateTime<Local>,
     ) -> Result<Vec<CompletedFrame>, Self::FrameStoreError>;
 }
+
+#[cfg(test)]
+mod frame_edit_tests {
+    use super::*;
+
+    fn make_edit(project: &str, start: &str, stop: Option<&str>) -> FrameEdit {
+        FrameEdit {
+            project: project.to_string(),
+            start: start.to_string(),
+            stop: stop.map(|s| s.to_string()),
+            tags: vec!["tag".to_string(), "".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_validate_valid_edit() {
+        let edit = make_edit(
+            "project",
+            "2025-01-01 10:00:00",
+            Some("2025-01-01 11:00:00"),
+        );
+        let valid = edit.validate(true).expect("Edit should be valid");
+        assert_eq!(valid.project().to_string(), "project".magenta().to_string());
+        assert!(valid.stop().is_some());
+        assert_eq!(valid.tags().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_invalid_date_format() {
+        let edit = make_edit("project", "2025-01-01 10:00", None);
+        assert_eq!(
+            edit.validate(false).unwrap_err(),
+            FrameEditError::InvalidDateFormat("2025-01-01 10:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_start_after_stop() {
+        let edit = make_edit(
+            "project",
+            "2025-01-01 12:00:00",
+            Some("2025-01-01 11:00:00"),
+        );
+        assert_eq!(
+            edit.validate(true).unwrap_err(),
+            FrameEditError::StartAfterStop
+        );
+    }
+
+    #[test]
+    fn test_validate_stop_in_future() {
+        let stop = (Local::now() + Duration::hours(1))
+            .format(EDIT_DATETIME_FORMAT)
+            .to_string();
+        let edit = make_edit("project", "2025-01-01 12:00:00", Some(&stop));
+        assert_eq!(
+            edit.validate(true).unwrap_err(),
+            FrameEditError::StopInFuture
+        );
+    }
+
+    #[test]
+    fn test_validate_empty_project() {
+        let edit = make_edit("  ", "2025-01-01 12:00:00", None);
+        assert_eq!(
+            edit.validate(false).unwrap_err(),
+            FrameEditError::EmptyProject
+        );
+    }
+
+    #[test]
+    fn test_validate_project_with_empty_level() {
+        assert!(ProjectName::new("client/area").is_some());
+        for project in ["client/", "/client", "client//area", "client/ /area"] {
+            let edit = make_edit(project, "2025-01-01 12:00:00", None);
+            assert_eq!(
+                edit.validate(false).unwrap_err(),
+                FrameEditError::InvalidProject(project.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_missing_stop() {
+        let edit = make_edit("project", "2025-01-01 12:00:00", None);
+        assert_eq!(
+            edit.validate(true).unwrap_err(),
+            FrameEditError::MissingStop
+        );
+        assert!(edit.validate(false).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod frame_tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_id_is_uuid_hex() {
+        let id = generate_id();
+        assert_eq!(id.len(), 32);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_generate_id_is_unique() {
+        let ids: std::collections::HashSet<String> = (0..1000).map(|_| generate_id()).collect();
+        assert_eq!(ids.len(), 1000);
+    }
+}
+
+#[cfg(test)]
+mod find_frame_tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn make_frame(id: &str, start_hour: u32) -> CompletedFrame {
+        let start = Local
+            .with_ymd_and_hms(2025, 1, 1, start_hour, 0, 0)
+            .unwrap();
+        Frame::new(
+            NonEmptyString::new("project").unwrap().into(),
+            Some(id.to_string()),
+            Some(start),
+            None,
+            vec![],
+            None,
+        )
+        .set_end(start + Duration::hours(1))
+    }
+
+    fn make_frames() -> Vec<CompletedFrame> {
+        vec![
+            make_frame("abcd123", 9),
+            make_frame("abce456", 10),
+            make_frame("abce789", 11),
+        ]
+    }
+
+    #[test]
+    fn test_find_frame_by_full_id() {
+        let frames = make_frames();
+        let frame = find_frame(&frames, "abce456").unwrap();
+        assert_eq!(frame.frame().id(), "abce456");
+    }
+
+    #[test]
+    fn test_find_frame_by_unique_prefix() {
+        let frames = make_frames();
+        let frame = find_frame(&frames, "abcd").unwrap();
+        assert_eq!(frame.frame().id(), "abcd123");
+    }
+
+    #[test]
+    fn test_find_frame_by_ambiguous_prefix() {
+        let frames = make_frames();
+        match find_frame(&frames, "abce") {
+            Err(FrameReferenceError::Ambiguous(_, candidates)) => assert_eq!(candidates.len(), 2),
+            result => panic!("Expected ambiguous reference, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_find_frame_by_position() {
+        let frames = make_frames();
+        assert_eq!(find_frame(&frames, "-1").unwrap().frame().id(), "abce789");
+        assert_eq!(find_frame(&frames, "-3").unwrap().frame().id(), "abcd123");
+        assert!(find_frame(&frames, "-4").is_err());
+        assert!(find_frame(&frames, "-0").is_err());
+    }
+
+    #[test]
+    fn test_find_frame_not_found() {
+        let frames = make_frames();
+        assert!(matches!(
+            find_frame(&frames, "wxyz"),
+            Err(FrameReferenceError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_find_frame_by_too_short_reference() {
+        let frames = vec![make_frame("abcd123", 9)];
+        for reference in ["", "a", "abc"] {
+            assert!(matches!(
+                find_frame(&frames, reference),
+                Err(FrameReferenceError::TooShort(_))
+            ));
+        }
+        // Full ids are found whatever their length
+        let frames = vec![make_frame("ab", 9)];
+        assert_eq!(find_frame(&frames, "ab").unwrap().frame().id(), "ab");
+    }
+}
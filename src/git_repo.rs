@@ -0,0 +1,315 @@
+// The git repository in which work is tracked: its name and branch as tags of started frames, the
+// project of its `.watsup` file and the `post-checkout` hook which switches the branch tag
+//
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Deserialize;
+
+/// The file in a repository, or any directory, which gives the project of frames started in it
+pub const PROJECT_FILE: &str = ".watsup";
+
+/// The prefixes of the tags added for the repository and the branch
+const REPOSITORY_TAG_PREFIX: &str = "repo:";
+const BRANCH_TAG_PREFIX: &str = "branch:";
+
+/// The line which marks a `post-checkout` hook written by `watsup git-hook install`
+const HOOK_MARKER: &str = "# Written by `watsup git-hook install`";
+
+const POST_CHECKOUT_HOOK: &str = r#"#!/bin/sh
+# Written by `watsup git-hook install`
+# Switches the branch tag of the current frame when a branch is checked out
+if [ "$3" = "1" ]; then
+    watsup git-hook checkout
+fi
+"#;
+
+#[derive(Debug)]
+pub enum GitRepoError {
+    /// No project was given and no `.watsup` file names one
+    NoProject,
+    /// The `.watsup` file can not be read
+    InvalidProjectFile(PathBuf, String),
+    /// The directory is not in a git repository
+    NoRepository,
+    /// A `post-checkout` hook which was not written by watsup exists
+    HookExists(PathBuf),
+    IO(std::io::Error),
+}
+
+impl Display for GitRepoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitRepoError::NoProject => write!(
+                f,
+                "No project given and no {} file with a project found",
+                PROJECT_FILE
+            ),
+            GitRepoError::InvalidProjectFile(path, details) => {
+                write!(f, "Invalid project file {}: {}", path.display(), details)
+            }
+            GitRepoError::NoRepository => write!(f, "Not in a git repository"),
+            GitRepoError::HookExists(path) => write!(
+                f,
+                "Hook {} exists already, use --force to replace it",
+                path.display()
+            ),
+            GitRepoError::IO(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for GitRepoError {
+    fn from(e: std::io::Error) -> Self {
+        GitRepoError::IO(e)
+    }
+}
+
+/// The content of a `.watsup` file
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ProjectFile {
+    project: String,
+    /// Tags added to the frames of the project
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// The git repository of a directory
+#[derive(Debug, PartialEq)]
+pub struct Repository {
+    root: PathBuf,
+    /// The name of the root directory
+    name: String,
+    /// `None` if no branch is checked out
+    branch: Option<String>,
+}
+
+impl Repository {
+    /// The repository which contains the directory. `None` outside of a repository or if git is
+    /// not installed.
+    pub fn find(dir: &Path) -> Option<Self> {
+        let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?);
+        let name = root.file_name()?.to_string_lossy().into_owned();
+        let branch = git(dir, &["symbolic-ref", "--short", "-q", "HEAD"]);
+        Some(Self { root, name, branch })
+    }
+
+    /// The tags of frames tracked in the repository, e.g. `repo:watsup` and `branch:main`
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = vec![format!("{}{}", REPOSITORY_TAG_PREFIX, self.name)];
+        if let Some(branch) = &self.branch {
+            tags.push(format!("{}{}", BRANCH_TAG_PREFIX, branch));
+        }
+        tags
+    }
+
+    /// The tags of a frame in the repository after the branch was switched, or `None` if the
+    /// frame is not tracked in this repository or already has the tag of the branch
+    pub fn switch_branch_tag(&self, tags: &[String]) -> Option<Vec<String>> {
+        let repository_tag = format!("{}{}", REPOSITORY_TAG_PREFIX, self.name);
+        if !tags.contains(&repository_tag) {
+            return None;
+        }
+        let switched: Vec<String> = tags
+            .iter()
+            .filter(|tag| !tag.starts_with(BRANCH_TAG_PREFIX))
+            .cloned()
+            .chain(self.tags().into_iter().skip(1))
+            .collect();
+        (switched != tags).then_some(switched)
+    }
+}
+
+/// Run git in the directory and return its trimmed output, `None` if it failed
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    log::debug!("Running git. args={:?}", args);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Find the `.watsup` file in the directory or its parents, up to the root of the repository
+fn find_project_file(dir: &Path, repository: Option<&Repository>) -> Option<PathBuf> {
+    for ancestor in dir.ancestors() {
+        let path = ancestor.join(PROJECT_FILE);
+        if path.is_file() {
+            return Some(path);
+        }
+        if repository.is_some_and(|repository| ancestor == repository.root) {
+            break;
+        }
+    }
+    None
+}
+
+fn read_project_file(path: &Path) -> Result<ProjectFile, GitRepoError> {
+    let invalid = |details: String| GitRepoError::InvalidProjectFile(path.to_path_buf(), details);
+    let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    toml::from_str(&content).map_err(|e| invalid(e.to_string()))
+}
+
+/// The project and tags of a frame started in the directory. Without a project, the project and
+/// tags of the `.watsup` file are used. With `auto_tag`, the tags of the repository are added.
+pub fn start_project_and_tags(
+    dir: &Path,
+    project: Option<&str>,
+    tags: &[String],
+    auto_tag: bool,
+) -> Result<(String, Vec<String>), GitRepoError> {
+    // Finding the repository runs git, which is only needed for the `.watsup` file or the tags
+    let repository = if auto_tag || project.is_none() {
+        Repository::find(dir)
+    } else {
+        None
+    };
+    let mut tags = tags.to_vec();
+    let project = match project {
+        Some(project) => project.to_string(),
+        None => {
+            let path =
+                find_project_file(dir, repository.as_ref()).ok_or(GitRepoError::NoProject)?;
+            let project_file = read_project_file(&path)?;
+            tags.extend(project_file.tags);
+            project_file.project
+        }
+    };
+    if auto_tag && let Some(repository) = repository {
+        tags.extend(repository.tags());
+    }
+    let mut seen = HashSet::new();
+    tags.retain(|tag| seen.insert(tag.clone()));
+    Ok((project, tags))
+}
+
+/// Write the `post-checkout` hook into the repository of the directory. Returns the path of the
+/// hook. A hook not written by watsup is only replaced with `force`.
+pub fn install_hook(dir: &Path, force: bool) -> Result<PathBuf, GitRepoError> {
+    let hooks_dir =
+        git(dir, &["rev-parse", "--git-path", "hooks"]).ok_or(GitRepoError::NoRepository)?;
+    let path = dir.join(hooks_dir).join("post-checkout");
+    if !force
+        && let Ok(content) = std::fs::read_to_string(&path)
+        && !content.contains(HOOK_MARKER)
+    {
+        return Err(GitRepoError::HookExists(path));
+    }
+    std::fs::create_dir_all(path.parent().expect("The hook is in the hooks directory"))?;
+    std::fs::write(&path, POST_CHECKOUT_HOOK)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_repository(branch: &str) -> tempfile::TempDir {
+        let dir = tempfile::Builder::new().prefix("repo").tempdir().unwrap();
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(["init", "--quiet", "-b", branch])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        dir
+    }
+
+    #[test]
+    fn test_repository_tags() {
+        let dir = init_repository("feature/x");
+        let repository = Repository::find(dir.path()).unwrap();
+        let name = dir.path().file_name().unwrap().to_str().unwrap();
+        assert_eq!(
+            repository.tags(),
+            vec![format!("repo:{}", name), "branch:feature/x".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_project_file_gives_project_and_tags() {
+        let dir = init_repository("main");
+        std::fs::write(
+            dir.path().join(PROJECT_FILE),
+            "project = \"watsup\"\ntags = [\"rust\"]\n",
+        )
+        .unwrap();
+        let subdir = dir.path().join("src");
+        std::fs::create_dir(&subdir).unwrap();
+
+        let (project, tags) = start_project_and_tags(&subdir, None, &[], true).unwrap();
+        assert_eq!(project, "watsup");
+        assert_eq!(tags[0], "rust");
+        assert_eq!(tags[2], "branch:main");
+
+        let (project, tags) =
+            start_project_and_tags(&subdir, Some("other"), &["a".to_string()], false).unwrap();
+        assert_eq!(project, "other");
+        assert_eq!(tags, vec!["a"]);
+    }
+
+    #[test]
+    fn test_no_project_without_project_file() {
+        let dir = init_repository("main");
+        assert!(matches!(
+            start_project_and_tags(dir.path(), None, &[], true),
+            Err(GitRepoError::NoProject)
+        ));
+    }
+
+    #[test]
+    fn test_switch_branch_tag() {
+        let repository = Repository {
+            root: PathBuf::from("/src/watsup"),
+            name: "watsup".to_string(),
+            branch: Some("fix".to_string()),
+        };
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            repository.switch_branch_tag(&tags(&["a", "repo:watsup", "branch:main"])),
+            Some(tags(&["a", "repo:watsup", "branch:fix"]))
+        );
+        assert_eq!(
+            repository.switch_branch_tag(&tags(&["repo:watsup", "branch:fix"])),
+            None
+        );
+        assert_eq!(repository.switch_branch_tag(&tags(&["repo:other"])), None);
+    }
+
+    #[test]
+    fn test_install_hook_keeps_foreign_hooks() {
+        let dir = init_repository("main");
+        let path = install_hook(dir.path(), false).unwrap();
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains(HOOK_MARKER)
+        );
+        // The hook written by watsup is replaced
+        install_hook(dir.path(), false).unwrap();
+
+        std::fs::write(&path, "#!/bin/sh\necho mine\n").unwrap();
+        assert!(matches!(
+            install_hook(dir.path(), false),
+            Err(GitRepoError::HookExists(_))
+        ));
+        install_hook(dir.path(), true).unwrap();
+    }
+}
@@ -0,0 +1,203 @@
+// Sync of the frames through a git repository in the watson directory
+//
+
+use std::{fmt::Display, path::Path, process::Command};
+
+use crate::{
+    frame::{CompletedFrame, FrameStore},
+    merge::{self, MergeChanges},
+};
+
+/// The file in the watson directory which is tracked by git. The state and the last sync time
+/// belong to one machine only and are not committed.
+const FRAMES_FILE: &str = "frames";
+
+/// The message of the commit of local changes to the frames before pulling, e.g. of frames edited
+/// by hand
+pub const LOCAL_FRAMES_MESSAGE: &str = "Commit local frames";
+
+#[derive(Debug)]
+pub enum GitError {
+    /// git could not be run at all
+    Command(std::io::Error),
+    /// git exited with an error
+    Failed(String, String),
+    /// The frames file of a revision could not be read
+    InvalidFrames(String, serde_json::Error),
+    /// The merged frames could not be saved
+    SaveFrames(String),
+}
+
+impl Display for GitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitError::Command(e) => write!(f, "Failed to run git: {}", e),
+            GitError::Failed(command, stderr) => {
+                write!(f, "git {} failed: {}", command, stderr.trim())
+            }
+            GitError::InvalidFrames(revision, e) => {
+                write!(f, "Invalid frames file in {}: {}", revision, e)
+            }
+            GitError::SaveFrames(e) => write!(f, "Failed to save the merged frames: {}", e),
+        }
+    }
+}
+
+/// How the local branch relates to its upstream branch
+#[derive(Debug, PartialEq)]
+pub enum Divergence {
+    UpToDate,
+    /// Only the local branch has new commits
+    Ahead,
+    /// Only the upstream branch has new commits
+    Behind,
+    /// Both branches have new commits
+    Diverged,
+}
+
+/// Run git in the given directory and return its standard output
+fn git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    log::debug!("Running git. args={:?}", args);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(GitError::Command)?;
+    if !output.status.success() {
+        return Err(GitError::Failed(
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Commit the frames file if it changed. Returns whether a commit was created.
+pub fn commit(dir: &Path, message: &str) -> Result<bool, GitError> {
+    git(dir, &["add", FRAMES_FILE])?;
+    // `diff --quiet` fails if there are staged changes
+    if git(dir, &["diff", "--cached", "--quiet"]).is_ok() {
+        return Ok(false);
+    }
+    git(dir, &["commit", "--quiet", "-m", message])?;
+    Ok(true)
+}
+
+pub fn fetch(dir: &Path) -> Result<(), GitError> {
+    git(dir, &["fetch", "--quiet"]).map(|_| ())
+}
+
+pub fn push(dir: &Path) -> Result<(), GitError> {
+    git(dir, &["push", "--quiet"]).map(|_| ())
+}
+
+/// Whether the current branch has an upstream branch to pull from
+pub fn has_upstream(dir: &Path) -> bool {
+    git(dir, &["rev-parse", "--abbrev-ref", "@{u}"]).is_ok()
+}
+
+pub fn divergence(dir: &Path) -> Result<Divergence, GitError> {
+    let counts = git(dir, &["rev-list", "--left-right", "--count", "HEAD...@{u}"])?;
+    let counts: Vec<&str> = counts.split_whitespace().collect();
+    Ok(match counts[..] {
+        ["0", "0"] => Divergence::UpToDate,
+        [_, "0"] => Divergence::Ahead,
+        ["0", _] => Divergence::Behind,
+        _ => Divergence::Diverged,
+    })
+}
+
+pub fn fast_forward(dir: &Path) -> Result<(), GitError> {
+    git(dir, &["merge", "--quiet", "--ff-only", "@{u}"]).map(|_| ())
+}
+
+/// Read the frames of a revision. A revision without frames file has no frames.
+fn frames_at(dir: &Path, revision: &str) -> Result<Vec<CompletedFrame>, GitError> {
+    let object = format!("{}:{}", revision, FRAMES_FILE);
+    if git(dir, &["cat-file", "-e", &object]).is_err() {
+        return Ok(Vec::new());
+    }
+    let content = git(dir, &["show", &object])?;
+    merge::parse_watson_frames(&content)
+        .map_err(|e| GitError::InvalidFrames(revision.to_string(), e))
+}
+
+/// The frames of the common ancestor of the local and the upstream branch, and of the upstream
+/// branch
+pub fn base_and_upstream_frames(
+    dir: &Path,
+) -> Result<(Vec<CompletedFrame>, Vec<CompletedFrame>), GitError> {
+    let base = match git(dir, &["merge-base", "HEAD", "@{u}"]) {
+        Ok(base) => frames_at(dir, base.trim())?,
+        // Unrelated histories have no common frames
+        Err(_) => Vec::new(),
+    };
+    Ok((base, frames_at(dir, "@{u}")?))
+}
+
+/// Start a merge of the upstream branch which keeps the local files. The merged frames are
+/// written by the caller before `finish_merge` is called, so git never merges the frames file as
+/// text.
+pub fn start_merge(dir: &Path) -> Result<(), GitError> {
+    git(
+        dir,
+        &[
+            "merge",
+            "--quiet",
+            "--no-commit",
+            "--allow-unrelated-histories",
+            "-s",
+            "ours",
+            "@{u}",
+        ],
+    )
+    .map(|_| ())
+}
+
+pub fn finish_merge(dir: &Path) -> Result<(), GitError> {
+    git(dir, &["add", FRAMES_FILE])?;
+    git(dir, &["commit", "--quiet", "-m", "Merge frames"]).map(|_| ())
+}
+
+pub fn abort_merge(dir: &Path) -> Result<(), GitError> {
+    git(dir, &["merge", "--abort"]).map(|_| ())
+}
+
+/// Commit the local frames and bring in the frames of the upstream branch, like `git pull`, but
+/// merging diverged frames one by one instead of as text. Returns how the branches related before
+/// the pull, and the changes to the local frames if they were merged.
+pub fn pull<T: FrameStore>(
+    dir: &Path,
+    store: &T,
+    message: &str,
+) -> Result<(Divergence, Option<MergeChanges>), GitError> {
+    commit(dir, message)?;
+    fetch(dir)?;
+    let divergence = divergence(dir)?;
+    let changes = match divergence {
+        Divergence::UpToDate | Divergence::Ahead => None,
+        Divergence::Behind => {
+            fast_forward(dir)?;
+            None
+        }
+        Divergence::Diverged => {
+            let (base, upstream) = base_and_upstream_frames(dir)?;
+            let local = store
+                .get_all_frames()
+                .map_err(|e| GitError::SaveFrames(e.to_string()))?;
+            let changes = merge::three_way_merge(&base, &local, &upstream);
+            start_merge(dir)?;
+            if !changes.is_empty()
+                && let Err(e) =
+                    store.update_frames(&changes.insert, &changes.update, &changes.delete)
+            {
+                let _ = abort_merge(dir);
+                return Err(GitError::SaveFrames(e.to_string()));
+            }
+            finish_merge(dir)?;
+            Some(changes)
+        }
+    };
+    Ok((divergence, changes))
+}
@@ -0,0 +1,252 @@
+// The hooks, executables in `<data dir>/hooks/` which are run before and after frames are started,
+// stopped, edited or canceled, e.g. to post to a chat
+//
+
+use std::{
+    fmt::Display,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::{frame::CompletedFrame, state::OngoingFrame};
+
+/// The commands for which hooks are run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    Start,
+    Stop,
+    Edit,
+    Cancel,
+}
+
+impl Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HookEvent::Start => "start",
+            HookEvent::Stop => "stop",
+            HookEvent::Edit => "edit",
+            HookEvent::Cancel => "cancel",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug)]
+pub enum HookError {
+    /// The hook could not be run
+    Failed(String, String),
+    /// The hook exited with a non-zero status
+    ExitStatus(String, String),
+}
+
+impl Display for HookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookError::Failed(hook, details) => write!(f, "Hook {} failed: {}", hook, details),
+            HookError::ExitStatus(hook, status) => {
+                write!(f, "Hook {} exited with {}", hook, status)
+            }
+        }
+    }
+}
+
+/// The frame given to a hook as JSON on stdin. Started frames have no id and no stop yet.
+#[derive(Debug, Serialize)]
+pub struct HookFrame {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    project: String,
+    start: DateTime<Local>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<DateTime<Local>>,
+    tags: Vec<String>,
+}
+
+impl HookFrame {
+    /// The frame with the time at which it is stopped
+    pub fn stopped_at(self, stop: DateTime<Local>) -> Self {
+        Self {
+            stop: Some(stop),
+            ..self
+        }
+    }
+
+    /// The environment variables which are set for the hook, next to the `WATSUP_HOOK` name
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("WATSUP_PROJECT", self.project.clone()),
+            ("WATSUP_TAGS", self.tags.join(",")),
+            ("WATSUP_START", self.start.to_rfc3339()),
+        ];
+        if let Some(stop) = self.stop {
+            env.push(("WATSUP_STOP", stop.to_rfc3339()));
+        }
+        if let Some(id) = &self.id {
+            env.push(("WATSUP_FRAME_ID", id.clone()));
+        }
+        env
+    }
+}
+
+impl From<&OngoingFrame> for HookFrame {
+    fn from(ongoing_frame: &OngoingFrame) -> Self {
+        Self {
+            id: None,
+            project: ongoing_frame.project().as_str().to_string(),
+            start: *ongoing_frame.start(),
+            stop: None,
+            tags: ongoing_frame.tags().iter().map(|t| t.to_string()).collect(),
+        }
+    }
+}
+
+impl From<&CompletedFrame> for HookFrame {
+    fn from(completed_frame: &CompletedFrame) -> Self {
+        let frame = completed_frame.frame();
+        Self {
+            id: Some(frame.id().to_string()),
+            project: frame.project().as_str().to_string(),
+            start: *frame.start(),
+            stop: Some(completed_frame.end()),
+            tags: frame.tags().iter().map(|t| t.to_string()).collect(),
+        }
+    }
+}
+
+/// The directory of the hooks in the watson directory
+pub fn hooks_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("hooks")
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Run the hook with the name if it exists, with the frame as JSON on stdin. Hooks which are
+/// not executable are skipped, like in git.
+fn run_hook(data_dir: &Path, name: &str, frame: &HookFrame) -> Result<(), HookError> {
+    let path = hooks_dir(data_dir).join(name);
+    if !path.exists() {
+        return Ok(());
+    }
+    if !is_executable(&path) {
+        log::warn!("Skipping hook which is not executable. path={:?}", path);
+        return Ok(());
+    }
+    log::info!("Running hook. path={:?}", path);
+    let failed = |e: std::io::Error| HookError::Failed(name.to_string(), e.to_string());
+    let json = serde_json::to_string(frame)
+        .map_err(|e| HookError::Failed(name.to_string(), e.to_string()))?;
+    let mut child = Command::new(&path)
+        .current_dir(data_dir)
+        .env("WATSUP_HOOK", name)
+        .envs(frame.env())
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(failed)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks which do not read the frame may exit before it is written
+        if let Err(e) = writeln!(stdin, "{}", json)
+            && e.kind() != std::io::ErrorKind::BrokenPipe
+        {
+            return Err(failed(e));
+        }
+    }
+    let status = child.wait().map_err(failed)?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(HookError::ExitStatus(name.to_string(), status.to_string())),
+    }
+}
+
+/// Run the `pre-` hook of the event. An error means that the command must not be executed.
+pub fn run_pre_hook(data_dir: &Path, event: HookEvent, frame: &HookFrame) -> Result<(), HookError> {
+    run_hook(data_dir, &format!("pre-{}", event), frame)
+}
+
+/// Run the `post-` hook of the event, after the command was executed
+pub fn run_post_hook(
+    data_dir: &Path,
+    event: HookEvent,
+    frame: &HookFrame,
+) -> Result<(), HookError> {
+    run_hook(data_dir, &format!("post-{}", event), frame)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+    use crate::common::NonEmptyString;
+
+    fn write_hook(data_dir: &Path, name: &str, script: &str, mode: u32) {
+        let dir = hooks_dir(data_dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    fn create_hook_frame() -> HookFrame {
+        let ongoing_frame = OngoingFrame::new(
+            NonEmptyString::new("project").unwrap().into(),
+            Local::now(),
+            vec![
+                NonEmptyString::new("a").unwrap(),
+                NonEmptyString::new("b").unwrap(),
+            ],
+        );
+        HookFrame::from(&ongoing_frame)
+    }
+
+    #[test]
+    fn test_hook_gets_frame_on_stdin_and_in_env() {
+        let dir = tempfile::tempdir().unwrap();
+        write_hook(
+            dir.path(),
+            "post-start",
+            "cat > frame.json; echo \"$WATSUP_HOOK $WATSUP_PROJECT $WATSUP_TAGS\" > env.txt",
+            0o755,
+        );
+        run_post_hook(dir.path(), HookEvent::Start, &create_hook_frame()).unwrap();
+
+        let frame: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join("frame.json")).unwrap())
+                .unwrap();
+        assert_eq!(frame["project"], "project");
+        assert!(frame.get("stop").is_none());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("env.txt")).unwrap(),
+            "post-start project a,b\n"
+        );
+    }
+
+    #[test]
+    fn test_failing_hook_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        write_hook(dir.path(), "pre-stop", "exit 3", 0o755);
+        let error = run_pre_hook(dir.path(), HookEvent::Stop, &create_hook_frame()).unwrap_err();
+        assert!(matches!(error, HookError::ExitStatus(hook, _) if hook == "pre-stop"));
+    }
+
+    #[test]
+    fn test_missing_and_not_executable_hooks_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(run_pre_hook(dir.path(), HookEvent::Edit, &create_hook_frame()).is_ok());
+        write_hook(dir.path(), "pre-edit", "exit 1", 0o644);
+        assert!(run_pre_hook(dir.path(), HookEvent::Edit, &create_hook_frame()).is_ok());
+    }
+}
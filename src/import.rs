@@ -0,0 +1,444 @@
+// Import of frames from other time trackers
+//
+
+use std::{collections::HashSet, fmt::Display};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::{
+    common::NonEmptyString,
+    frame::{CompletedFrame, Frame, ProjectName},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ImportFormat {
+    /// The JSON output of `timew export`. The first tag is used as project.
+    Timewarrior,
+    /// The CSV export of the detailed report of Toggl Track
+    TogglCsv,
+    /// The CSV export of the detailed report of Clockify
+    ClockifyCsv,
+}
+
+/// The order of day and month in dates like `03/04/2025`, which CSV exports write depending on the
+/// user's locale settings
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DateOrder {
+    /// `MM/DD/YYYY`, e.g. in the US
+    MonthFirst,
+    /// `DD/MM/YYYY`, e.g. in most of Europe
+    DayFirst,
+}
+
+impl DateOrder {
+    fn format(self) -> &'static str {
+        match self {
+            DateOrder::MonthFirst => "%m/%d/%Y",
+            DateOrder::DayFirst => "%d/%m/%Y",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Json(serde_json::Error),
+    Csv(csv::Error),
+    MissingColumn(&'static str),
+    InvalidEntry(usize, String),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Json(e) => write!(f, "Invalid JSON: {}", e),
+            ImportError::Csv(e) => write!(f, "Invalid CSV: {}", e),
+            ImportError::MissingColumn(column) => write!(f, "Missing column \"{}\"", column),
+            ImportError::InvalidEntry(entry, details) => {
+                write!(f, "Invalid entry {}: {}", entry, details)
+            }
+        }
+    }
+}
+
+/// The frames read from an import file
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub frames: Vec<CompletedFrame>,
+    /// Entries which can not be imported as frame, with the reason why
+    pub skipped: Vec<String>,
+}
+
+/// Parse the content of an import file in the given format. Without `date_order`, dates with
+/// slashes are only accepted if just one order of day and month gives a valid date.
+pub fn parse(
+    format: ImportFormat,
+    content: &str,
+    date_order: Option<DateOrder>,
+) -> Result<ParsedImport, ImportError> {
+    match format {
+        ImportFormat::Timewarrior => parse_timewarrior(content),
+        ImportFormat::TogglCsv => parse_csv(content, &TOGGL_COLUMNS, date_order),
+        ImportFormat::ClockifyCsv => parse_csv(content, &CLOCKIFY_COLUMNS, date_order),
+    }
+}
+
+/// Split `frames` into the frames which are not yet in `existing` and the ones which are.
+/// Two frames are the same if they have the same start, end and project.
+pub fn deduplicate(
+    frames: Vec<CompletedFrame>,
+    existing: &[CompletedFrame],
+) -> (Vec<CompletedFrame>, Vec<CompletedFrame>) {
+    let key = |f: &CompletedFrame| {
+        (
+            f.frame().start().timestamp(),
+            f.end().timestamp(),
+            f.frame().project().as_str().to_string(),
+        )
+    };
+    let mut known: HashSet<_> = existing.iter().map(key).collect();
+    frames.into_iter().partition(|f| known.insert(key(f)))
+}
+
+/// The frame of an entry, or why the entry can not be imported. The reason follows the entry
+/// number in the skipped message, like "has no project".
+fn make_frame(
+    project: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    tags: Vec<NonEmptyString>,
+) -> Result<CompletedFrame, String> {
+    let project = project.trim();
+    if project.is_empty() {
+        return Err("has no project".to_string());
+    }
+    let project = ProjectName::new(project)
+        .ok_or_else(|| format!("has the invalid project name \"{}\"", project))?;
+    if end < start {
+        return Err("ends before it starts".to_string());
+    }
+    CompletedFrame::from_frame(Frame::new(
+        project,
+        None,
+        Some(start),
+        Some(end),
+        tags,
+        None,
+    ))
+    .ok_or_else(|| "has no end".to_string())
+}
+
+#[derive(Deserialize)]
+struct TimewarriorInterval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+const TIMEWARRIOR_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+fn parse_timewarrior_datetime(entry: usize, date: &str) -> Result<DateTime<Local>, ImportError> {
+    NaiveDateTime::parse_from_str(date, TIMEWARRIOR_DATETIME_FORMAT)
+        .map(|naive| Utc.from_utc_datetime(&naive).with_timezone(&Local))
+        .map_err(|_| ImportError::InvalidEntry(entry, format!("Invalid date {}", date)))
+}
+
+fn parse_timewarrior(content: &str) -> Result<ParsedImport, ImportError> {
+    let intervals: Vec<TimewarriorInterval> =
+        serde_json::from_str(content).map_err(ImportError::Json)?;
+    let mut import = ParsedImport::default();
+    for (index, interval) in intervals.into_iter().enumerate() {
+        let entry = index + 1;
+        let Some(end) = interval.end else {
+            import
+                .skipped
+                .push(format!("Entry {} is still ongoing", entry));
+            continue;
+        };
+        let start = parse_timewarrior_datetime(entry, &interval.start)?;
+        let end = parse_timewarrior_datetime(entry, &end)?;
+        let mut tags = interval.tags.iter().filter_map(|t| NonEmptyString::new(t));
+        let Some(project) = tags.next() else {
+            import
+                .skipped
+                .push(format!("Entry {} has no tag to use as project", entry));
+            continue;
+        };
+        match make_frame(project.as_str(), start, end, tags.collect()) {
+            Ok(frame) => import.frames.push(frame),
+            Err(reason) => import.skipped.push(format!("Entry {} {}", entry, reason)),
+        }
+    }
+    Ok(import)
+}
+
+/// The names of the columns in a CSV export
+struct CsvColumns {
+    project: &'static str,
+    tags: &'static str,
+    start_date: &'static str,
+    start_time: &'static str,
+    end_date: &'static str,
+    end_time: &'static str,
+}
+
+const TOGGL_COLUMNS: CsvColumns = CsvColumns {
+    project: "Project",
+    tags: "Tags",
+    start_date: "Start date",
+    start_time: "Start time",
+    end_date: "End date",
+    end_time: "End time",
+};
+
+const CLOCKIFY_COLUMNS: CsvColumns = CsvColumns {
+    project: "Project",
+    tags: "Tags",
+    start_date: "Start Date",
+    start_time: "Start Time",
+    end_date: "End Date",
+    end_time: "End Time",
+};
+
+/// Date formats used by the CSV exports, depending on the user's locale settings. Dates with
+/// slashes are parsed according to the `DateOrder`.
+const CSV_DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%d.%m.%Y", "%d-%m-%Y"];
+/// Time formats used by the CSV exports, depending on the user's locale settings
+const CSV_TIME_FORMATS: [&str; 4] = ["%H:%M:%S", "%I:%M:%S %p", "%H:%M", "%I:%M %p"];
+
+/// Parse a date with slashes. Without an order, a date which is valid in both orders, like
+/// `03/04/2025`, is ambiguous.
+fn parse_slash_date(
+    entry: usize,
+    date: &str,
+    date_order: Option<DateOrder>,
+) -> Result<Option<NaiveDate>, ImportError> {
+    let parse = |order: DateOrder| NaiveDate::parse_from_str(date, order.format()).ok();
+    if let Some(order) = date_order {
+        return Ok(parse(order));
+    }
+    match (parse(DateOrder::MonthFirst), parse(DateOrder::DayFirst)) {
+        (Some(month_first), Some(day_first)) if month_first != day_first => {
+            Err(ImportError::InvalidEntry(
+                entry,
+                format!(
+                    "Ambiguous date {}, use --date-order to give the order of day and month",
+                    date
+                ),
+            ))
+        }
+        (month_first, day_first) => Ok(month_first.or(day_first)),
+    }
+}
+
+fn parse_csv_datetime(
+    entry: usize,
+    date: &str,
+    time: &str,
+    date_order: Option<DateOrder>,
+) -> Result<DateTime<Local>, ImportError> {
+    let invalid = || ImportError::InvalidEntry(entry, format!("Invalid date {} {}", date, time));
+    let date = match date.trim() {
+        slash_date if slash_date.contains('/') => parse_slash_date(entry, slash_date, date_order)?,
+        date => CSV_DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(date, format).ok()),
+    }
+    .ok_or_else(invalid)?;
+    let time = CSV_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(time.trim(), format).ok())
+        .ok_or_else(invalid)?;
+    Local
+        .from_local_datetime(&NaiveDateTime::new(date, time))
+        .earliest()
+        .ok_or_else(invalid)
+}
+
+fn parse_csv(
+    content: &str,
+    columns: &CsvColumns,
+    date_order: Option<DateOrder>,
+) -> Result<ParsedImport, ImportError> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers().map_err(ImportError::Csv)?.clone();
+    let column = |name: &'static str| {
+        headers
+            .iter()
+            // Exports may start with a byte order mark
+            .position(|header| header.trim_start_matches('\u{feff}') == name)
+            .ok_or(ImportError::MissingColumn(name))
+    };
+    let project_column = column(columns.project)?;
+    let tags_column = column(columns.tags)?;
+    let start_date_column = column(columns.start_date)?;
+    let start_time_column = column(columns.start_time)?;
+    let end_date_column = column(columns.end_date)?;
+    let end_time_column = column(columns.end_time)?;
+
+    let mut import = ParsedImport::default();
+    for (index, record) in reader.records().enumerate() {
+        let entry = index + 1;
+        let record = record.map_err(ImportError::Csv)?;
+        let field = |column: usize| record.get(column).unwrap_or_default();
+        let start = parse_csv_datetime(
+            entry,
+            field(start_date_column),
+            field(start_time_column),
+            date_order,
+        )?;
+        let end = parse_csv_datetime(
+            entry,
+            field(end_date_column),
+            field(end_time_column),
+            date_order,
+        )?;
+        let tags = field(tags_column)
+            .split(',')
+            .filter_map(|tag| NonEmptyString::new(tag.trim()))
+            .collect();
+        match make_frame(field(project_column), start, end, tags) {
+            Ok(frame) => import.frames.push(frame),
+            Err(reason) => import.skipped.push(format!("Entry {} {}", entry, reason)),
+        }
+    }
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Timelike};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_timewarrior() {
+        let content = r#"[
+            {"id":3,"start":"20250101T090000Z","end":"20250101T100000Z","tags":["project","tag"]},
+            {"id":2,"start":"20250101T110000Z","end":"20250101T120000Z"},
+            {"id":1,"start":"20250101T130000Z","tags":["ongoing"]}
+        ]"#;
+        let import = parse(ImportFormat::Timewarrior, content, None).unwrap();
+
+        assert_eq!(import.frames.len(), 1);
+        assert_eq!(import.skipped.len(), 2);
+        let frame = &import.frames[0];
+        assert_eq!(frame.frame().project().as_str(), "project");
+        assert_eq!(frame.frame().tags().len(), 1);
+        assert_eq!(frame.frame().start().with_timezone(&Utc).hour(), 9);
+        assert_eq!(frame.duration(), chrono::Duration::hours(1));
+    }
+
+    #[test]
+    fn test_parse_toggl_csv() {
+        let content = "\u{feff}User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+            Jane,jane@example.com,Client,Website,,Design,No,2025-01-01,09:00:00,2025-01-01,10:30:00,01:30:00,\"design, review\"\n\
+            Jane,jane@example.com,,,,Untracked,No,2025-01-01,11:00:00,2025-01-01,12:00:00,01:00:00,\n";
+        let import = parse(ImportFormat::TogglCsv, content, None).unwrap();
+
+        assert_eq!(import.frames.len(), 1);
+        assert_eq!(import.skipped.len(), 1);
+        let frame = &import.frames[0];
+        assert_eq!(frame.frame().project().as_str(), "Website");
+        assert_eq!(frame.frame().tags().len(), 2);
+        assert_eq!(frame.frame().tags()[1].as_str(), "review");
+        assert_eq!(frame.frame().start().hour(), 9);
+        assert_eq!(frame.duration(), chrono::Duration::minutes(90));
+    }
+
+    #[test]
+    fn test_parse_clockify_csv() {
+        let content = "Project,Client,Description,Task,User,Group,Email,Tags,Billable,Start Date,Start Time,End Date,End Time,Duration (h),Duration (decimal)\n\
+            Website,Client,Design,,Jane,,jane@example.com,design,Yes,01/15/2025,11:00:00 PM,01/16/2025,01:00:00 AM,02:00:00,2.00\n";
+        let import = parse(ImportFormat::ClockifyCsv, content, None).unwrap();
+
+        assert_eq!(import.frames.len(), 1);
+        let frame = &import.frames[0];
+        assert_eq!(frame.frame().start().hour(), 23);
+        assert_eq!(frame.end().hour(), 1);
+        assert_eq!(frame.duration(), chrono::Duration::hours(2));
+    }
+
+    #[test]
+    fn test_parse_csv_missing_column() {
+        let content = "Project,Start date\nWebsite,2025-01-01\n";
+        assert!(matches!(
+            parse(ImportFormat::TogglCsv, content, None),
+            Err(ImportError::MissingColumn("Tags"))
+        ));
+    }
+
+    #[test]
+    fn test_parse_csv_invalid_date() {
+        let content = "Project,Tags,Start date,Start time,End date,End time\n\
+            Website,,yesterday,09:00,2025-01-01,10:00\n";
+        assert!(matches!(
+            parse(ImportFormat::TogglCsv, content, None),
+            Err(ImportError::InvalidEntry(1, _))
+        ));
+    }
+
+    #[test]
+    fn test_parse_csv_slash_dates() {
+        let content = |date: &str| {
+            format!(
+                "Project,Tags,Start date,Start time,End date,End time\n\
+                Website,,{0},09:00,{0},10:00\n",
+                date
+            )
+        };
+        let start_day = |import: ParsedImport| import.frames[0].frame().start().day();
+
+        let import = parse(ImportFormat::TogglCsv, &content("31/01/2026"), None).unwrap();
+        assert_eq!(start_day(import), 31);
+        assert!(matches!(
+            parse(ImportFormat::TogglCsv, &content("03/04/2026"), None),
+            Err(ImportError::InvalidEntry(1, _))
+        ));
+        let day_first = Some(DateOrder::DayFirst);
+        let import = parse(ImportFormat::TogglCsv, &content("03/04/2026"), day_first).unwrap();
+        assert_eq!(start_day(import), 3);
+        let month_first = Some(DateOrder::MonthFirst);
+        let import = parse(ImportFormat::TogglCsv, &content("03/04/2026"), month_first).unwrap();
+        assert_eq!(start_day(import), 4);
+        assert!(parse(ImportFormat::TogglCsv, &content("31/01/2026"), month_first).is_err());
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        let content = r#"[
+            {"start":"20250101T090000Z","end":"20250101T100000Z","tags":["project"]},
+            {"start":"20250101T090000Z","end":"20250101T100000Z","tags":["project"]},
+            {"start":"20250101T090000Z","end":"20250101T100000Z","tags":["other"]}
+        ]"#;
+        let import = parse(ImportFormat::Timewarrior, content, None).unwrap();
+        let existing = vec![import.frames[2].clone()];
+
+        let (new, duplicates) = deduplicate(import.frames, &existing);
+
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].frame().project().as_str(), "project");
+        assert_eq!(duplicates.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_skips_invalid_entries() {
+        let content = r#"[
+            {"start":"20250101T100000Z","end":"20250101T090000Z","tags":["backwards"]},
+            {"start":"20250101T090000Z","end":"20250101T100000Z","tags":["client//area"]},
+            {"start":"20250101T090000Z","end":"20250101T100000Z","tags":["client/area"]}
+        ]"#;
+        let import = parse(ImportFormat::Timewarrior, content, None).unwrap();
+
+        assert_eq!(import.frames.len(), 1);
+        assert_eq!(
+            import.skipped,
+            vec![
+                "Entry 1 ends before it starts",
+                "Entry 2 has the invalid project name \"client//area\"",
+            ]
+        );
+    }
+}
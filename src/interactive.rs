@@ -0,0 +1,164 @@
+// The interactive menu of `watsup interactive`, which picks an action and a project with a fuzzy
+// finder on the terminal
+//
+
+use std::fmt::Display;
+
+use dialoguer::{FuzzySelect, Input, theme::ColorfulTheme};
+
+use crate::{cli::Command, frame::ProjectName};
+
+/// The entry of the project picker to type the name of a project which has no frames yet
+const NEW_PROJECT_ITEM: &str = "+ new project";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InteractiveAction {
+    DoNothing,
+    Start,
+    /// Start a frame and edit it right away, e.g. to set an earlier start
+    StartEdit,
+    Stop,
+    /// Stop the current frame and edit it right away
+    StopEdit,
+    Cancel,
+    Edit,
+    /// Stop the current frame, edit it and start a frame for another project without a gap
+    Change,
+}
+
+impl InteractiveAction {
+    /// All actions in the order in which they are offered
+    pub const ALL: [InteractiveAction; 8] = [
+        InteractiveAction::DoNothing,
+        InteractiveAction::Start,
+        InteractiveAction::StartEdit,
+        InteractiveAction::Stop,
+        InteractiveAction::StopEdit,
+        InteractiveAction::Cancel,
+        InteractiveAction::Edit,
+        InteractiveAction::Change,
+    ];
+
+    /// Whether a project has to be picked for the action
+    pub fn needs_project(&self) -> bool {
+        matches!(
+            self,
+            InteractiveAction::Start | InteractiveAction::StartEdit | InteractiveAction::Change
+        )
+    }
+
+    /// The commands which are executed for the action, in order. `project` is the picked project
+    /// of actions which need one. Without a project, these actions execute nothing.
+    pub fn commands(&self, project: Option<&str>) -> Vec<Command> {
+        if self.needs_project() && project.is_none() {
+            return Vec::new();
+        }
+        let start = |no_gap| {
+            project.map(|project| Command::Start {
+                project: Some(project.to_string()),
+                tags: Vec::new(),
+                no_gap,
+            })
+        };
+        let stop = Command::Stop { at: None };
+        let edit = Command::Edit {
+            id: None,
+            from: None,
+            to: None,
+            project: None,
+            format: None,
+        };
+        match self {
+            InteractiveAction::DoNothing => Vec::new(),
+            InteractiveAction::Start => start(false).into_iter().collect(),
+            InteractiveAction::StartEdit => start(false).into_iter().chain([edit]).collect(),
+            InteractiveAction::Stop => vec![stop],
+            InteractiveAction::StopEdit => vec![stop, edit],
+            InteractiveAction::Cancel => vec![Command::Cancel],
+            InteractiveAction::Edit => vec![edit],
+            InteractiveAction::Change => [stop, edit].into_iter().chain(start(true)).collect(),
+        }
+    }
+}
+
+impl Display for InteractiveAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            InteractiveAction::DoNothing => "do-nothing",
+            InteractiveAction::Start => "start",
+            InteractiveAction::StartEdit => "start-edit",
+            InteractiveAction::Stop => "stop",
+            InteractiveAction::StopEdit => "stop-edit",
+            InteractiveAction::Cancel => "cancel",
+            InteractiveAction::Edit => "edit",
+            InteractiveAction::Change => "change",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Let the user pick an action. Returns `None` if the picker was closed with Escape.
+pub fn pick_action() -> Result<Option<InteractiveAction>, dialoguer::Error> {
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select an action")
+        .items(InteractiveAction::ALL)
+        .default(0)
+        .interact_opt()?;
+    Ok(selection.map(|index| InteractiveAction::ALL[index]))
+}
+
+/// Let the user pick one of the projects or type the name of a new one. Returns `None` if the
+/// picker was closed with Escape or the typed name is empty.
+pub fn pick_project(projects: &[ProjectName]) -> Result<Option<String>, dialoguer::Error> {
+    let theme = ColorfulTheme::default();
+    let items: Vec<String> = projects
+        .iter()
+        .map(|project| project.as_str().to_string())
+        .chain([NEW_PROJECT_ITEM.to_string()])
+        .collect();
+    let Some(index) = FuzzySelect::with_theme(&theme)
+        .with_prompt("Select a project to start tracking")
+        .items(&items)
+        .default(0)
+        .interact_opt()?
+    else {
+        return Ok(None);
+    };
+    if index < projects.len() {
+        return Ok(Some(projects[index].as_str().to_string()));
+    }
+    let project: String = Input::with_theme(&theme)
+        .with_prompt("Name of the new project")
+        .allow_empty(true)
+        .interact_text()?;
+    let project = project.trim();
+    Ok((!project.is_empty()).then(|| project.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_stops_edits_and_starts_without_gap() {
+        let commands = InteractiveAction::Change.commands(Some("other"));
+        assert_eq!(commands.len(), 3);
+        assert!(matches!(commands[0], Command::Stop { at: None }));
+        assert!(matches!(commands[1], Command::Edit { id: None, .. }));
+        assert!(matches!(
+            &commands[2],
+            Command::Start { project, no_gap: true, .. } if project.as_deref() == Some("other")
+        ));
+    }
+
+    #[test]
+    fn test_start_without_project_starts_nothing() {
+        assert!(InteractiveAction::Start.commands(None).is_empty());
+        assert!(InteractiveAction::StartEdit.commands(None).is_empty());
+        assert!(InteractiveAction::Change.commands(None).is_empty());
+        assert!(matches!(
+            InteractiveAction::StopEdit.commands(None)[..],
+            [Command::Stop { .. }, Command::Edit { .. }]
+        ));
+    }
+}
@@ -1,8 +1,41 @@
 use std::{collections::HashMap, fmt::Display};
 
-use chrono::{DateTime, Local, NaiveTime};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime};
 
-use crate::frame::CompletedFrame;
+use crate::{frame::CompletedFrame, project_tree::ProjectTree};
+
+/// The beginning of the day of `time`
+pub fn beginning_of_day(time: DateTime<Local>) -> DateTime<Local> {
+    time.with_time(NaiveTime::MIN).earliest().unwrap_or(time)
+}
+
+/// The beginning of the week of `time`, weeks start on Monday
+pub fn beginning_of_week(time: DateTime<Local>) -> DateTime<Local> {
+    let days_since_monday = time.weekday().num_days_from_monday();
+    beginning_of_day(time - Duration::days(days_since_monday.into()))
+}
+
+/// Format a duration as `H:MM:SS`
+pub fn format_duration(duration: Duration) -> String {
+    format!(
+        "{}:{:02}:{:02}",
+        duration.num_hours(),
+        duration.num_minutes() % 60,
+        duration.num_seconds() % 60
+    )
+}
+
+/// The frames of one day in a log
+pub struct LogDay<'a> {
+    pub day: DateTime<Local>,
+    pub frames: Vec<&'a CompletedFrame>,
+}
+
+impl LogDay<'_> {
+    pub fn total(&self) -> Duration {
+        self.frames.iter().map(|f| f.duration()).sum()
+    }
+}
 
 pub struct FrameLog<'a> {
     frames: &'a [CompletedFrame],
@@ -26,25 +59,37 @@ impl<'a> FrameLog<'a> {
         }
         map
     }
+
+    /// The days of the log with their frames, the most recent day first
+    pub fn days(&self) -> Vec<LogDay<'a>> {
+        let mut days: Vec<LogDay<'a>> = self
+            .grouped_by_day()
+            .into_iter()
+            .map(|(day, frames)| LogDay { day, frames })
+            .collect();
+        days.sort_by_key(|day| std::cmp::Reverse(day.day));
+        days
+    }
+
+    /// The total duration of each project, rolled up into its parent projects
+    pub fn project_totals(&self) -> ProjectTree<'a> {
+        ProjectTree::from_frames(self.frames)
+    }
 }
 
 impl<'a> Display for FrameLog<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let grouped_by_day = self.grouped_by_day();
-        let mut days = grouped_by_day.keys().collect::<Vec<_>>();
-        days.sort_by(|a, b| b.cmp(a));
-        for day in days {
-            let frames = grouped_by_day.get(day).unwrap();
-            let total_duration = frames.iter().map(|f| f.duration()).reduce(|f1, f2| f1 + f2);
+        for day in self.days() {
+            let total_duration = day.total();
             writeln!(
                 f,
                 "{} ({}h {}min {}s)",
-                day.format("%A %d %B %Y"),
-                total_duration.unwrap().num_hours(),
-                total_duration.unwrap().num_minutes() - total_duration.unwrap().num_hours() * 60,
-                total_duration.unwrap().num_seconds() - total_duration.unwrap().num_minutes() * 60,
+                day.day.format("%A %d %B %Y"),
+                total_duration.num_hours(),
+                total_duration.num_minutes() - total_duration.num_hours() * 60,
+                total_duration.num_seconds() - total_duration.num_minutes() * 60,
             )?;
-            for frame in frames {
+            for frame in day.frames {
                 writeln!(f, "  {}", frame)?;
             }
         }
@@ -128,6 +173,55 @@ mod log_tests {
         assert!(grouped.get(&key2).unwrap()[0] == &frames[1]);
     }
 
+    #[test]
+    fn test_days_and_project_totals() {
+        use chrono::{Local, TimeZone};
+
+        let day1 = Local.with_ymd_and_hms(2025, 11, 22, 10, 0, 0).unwrap();
+        let day2 = Local.with_ymd_and_hms(2025, 11, 23, 10, 0, 0).unwrap();
+        let other = CompletedFrame::from_frame(Frame::new(
+            NonEmptyString::new("other").unwrap().into(),
+            None,
+            Some(day2 + Duration::hours(1)),
+            Some(day2 + Duration::hours(2)),
+            vec![],
+            None,
+        ))
+        .unwrap();
+        let frames = vec![
+            create_test_frame(day1, None),
+            create_test_frame(day2, None),
+            other,
+        ];
+        let log = FrameLog::new(&frames);
+
+        let days = log.days();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].day, beginning_of_day(day2));
+        assert_eq!(days[0].total(), Duration::minutes(75));
+
+        let totals = log.project_totals();
+        let totals = totals.roots();
+        assert_eq!(totals[0].name(), "other");
+        assert_eq!(totals[0].total(), Duration::hours(1));
+        assert_eq!(totals[1].total(), Duration::minutes(30));
+    }
+
+    #[test]
+    fn test_beginning_of_week_is_monday() {
+        use chrono::{Local, TimeZone};
+
+        let sunday = Local.with_ymd_and_hms(2025, 11, 23, 10, 0, 0).unwrap();
+        assert_eq!(
+            beginning_of_week(sunday),
+            Local.with_ymd_and_hms(2025, 11, 17, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            beginning_of_week(beginning_of_week(sunday)),
+            beginning_of_week(sunday)
+        );
+    }
+
     #[test]
     fn test_frame_across_midnight_is_in_starting_day_group() {
         use chrono::{Local, TimeZone};
@@ -1,16 +1,33 @@
-use std::{env, fs::OpenOptions, io, path::PathBuf};
+use std::{fs::OpenOptions, io};
 
 use ::log::{info, warn};
 use clap::Parser;
 use simplelog::{Config, WriteLogger};
 
+mod api;
+mod check;
 mod cli;
 mod cli_args;
 mod common;
+mod completions;
 mod config;
+#[cfg(unix)]
+mod daemon;
+mod edit_format;
+mod export;
 mod frame;
+mod git_repo;
+mod git_sync;
+mod hooks;
+mod import;
+mod interactive;
 mod log;
+mod merge;
+mod project_tree;
 mod state;
+mod status;
+mod sync;
+mod tui;
 mod stores {
     #[cfg(test)]
     pub mod in_memory_store;
@@ -20,8 +37,7 @@ mod stores {
 use cli::CommandExecutor;
 
 fn setup_logging() -> Result<(), io::Error> {
-    let home = PathBuf::from(env::var("HOME").unwrap());
-    let config_folder = home.join(".config/watsup");
+    let config_folder = config::get_config_folder();
     std::fs::create_dir_all(&config_folder)?;
     let log_file_path = config_folder.join("log.txt");
     let file = OpenOptions::new()
@@ -40,11 +56,37 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     setup_logging()?;
 
     let cli = cli::Cli::parse();
-    let config = config::Config::default();
-    let frame_store = stores::watson::Store::new(config);
+    let config = match config::Config::load() {
+        Ok(config) => config,
+        Err(error) => {
+            warn!("Loading config failed: {:?}", error);
+            println!("Error: {}", error);
+            return Ok(());
+        }
+    };
+    // Without a subcommand the interactive menu is shown
+    let command = cli.command.unwrap_or(cli::Command::Interactive);
 
-    let mut command_executor = CommandExecutor::new(&frame_store);
-    if let Err(error) = command_executor.execute_command(&cli.command) {
+    #[cfg(unix)]
+    if let Some(result) = daemon::Client::connect(&config::get_socket_path())
+        .and_then(|mut client| cli::execute_with_daemon(&mut client, &command, &config))
+    {
+        if let Err(error) = result {
+            warn!("Daemon command execution error: {:?}", error);
+            println!("Error: {}", error);
+        }
+        return Ok(());
+    }
+
+    let frame_store = match command {
+        // The servers keep the frames in memory between the requests
+        #[cfg(unix)]
+        cli::Command::Daemon => stores::watson::Store::with_cache(config.clone()),
+        cli::Command::Serve { .. } => stores::watson::Store::with_cache(config.clone()),
+        _ => stores::watson::Store::new(config.clone()),
+    };
+    let mut command_executor = CommandExecutor::new(&frame_store, config);
+    if let Err(error) = command_executor.execute_command(&command) {
         warn!("Command execution error: {:?}", error);
         println!("Error: {}", error);
     }
@@ -0,0 +1,281 @@
+// Merge of frames from another watson directory or machine
+//
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{frame::CompletedFrame, stores::watson};
+
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A frame which exists in both sets of frames, but with different content
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub local: CompletedFrame,
+    pub other: CompletedFrame,
+}
+
+impl Conflict {
+    /// The version of the frame which was edited last. The local frame wins a tie.
+    pub fn newer(&self) -> &CompletedFrame {
+        if self.other.frame().last_edit() > self.local.frame().last_edit() {
+            &self.other
+        } else {
+            &self.local
+        }
+    }
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Conflicting versions of frame {}:",
+            self.local.frame().id()
+        )?;
+        for (name, frame) in [("local", &self.local), ("other", &self.other)] {
+            let tags: Vec<&str> = frame.frame().tags().iter().map(|t| t.as_str()).collect();
+            writeln!(
+                f,
+                "  {:<5}  {}  {}  [{}]  edited {}",
+                name,
+                frame.frame().start().format("%Y-%m-%d"),
+                frame,
+                tags.join(", "),
+                frame.frame().last_edit().format(DATETIME_FORMAT)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of comparing frames from another source with the local frames
+#[derive(Debug, Default)]
+pub struct Merge {
+    /// Frames with an id which is unknown locally
+    pub new: Vec<CompletedFrame>,
+    /// Frames which exist locally, but with different content
+    pub conflicts: Vec<Conflict>,
+    /// Number of frames which are identical to the local ones
+    pub unchanged: usize,
+}
+
+/// Read frames in the format of watson's frames file
+pub fn parse_watson_frames(content: &str) -> Result<Vec<CompletedFrame>, serde_json::Error> {
+    let frames: Vec<watson::Frame> = serde_json::from_str(content)?;
+    Ok(frames.into_iter().map(CompletedFrame::from).collect())
+}
+
+/// Whether both frames describe the same time range, project and tags.
+/// The last edit time is ignored, as it does not change what was tracked.
+fn same_content(first: &CompletedFrame, second: &CompletedFrame) -> bool {
+    let tags = |frame: &CompletedFrame| -> Vec<String> {
+        frame
+            .frame()
+            .tags()
+            .iter()
+            .map(|t| t.as_str().to_string())
+            .collect()
+    };
+    first.frame().start().timestamp() == second.frame().start().timestamp()
+        && first.end().timestamp() == second.end().timestamp()
+        && first.frame().project() == second.frame().project()
+        && tags(first) == tags(second)
+}
+
+/// Compare the `other` frames with the `local` frames by their id
+pub fn merge_frames(local: &[CompletedFrame], other: Vec<CompletedFrame>) -> Merge {
+    let local: HashMap<&str, &CompletedFrame> = local.iter().map(|f| (f.frame().id(), f)).collect();
+    let mut merge = Merge::default();
+    for frame in other {
+        match local.get(frame.frame().id()) {
+            None => merge.new.push(frame),
+            Some(local_frame) if same_content(local_frame, &frame) => merge.unchanged += 1,
+            Some(local_frame) => merge.conflicts.push(Conflict {
+                local: (*local_frame).clone(),
+                other: frame,
+            }),
+        }
+    }
+    merge
+}
+
+/// The changes to apply to the local frames to get the result of a three-way merge
+#[derive(Debug, Default)]
+pub struct MergeChanges {
+    pub insert: Vec<CompletedFrame>,
+    pub update: Vec<CompletedFrame>,
+    pub delete: Vec<String>,
+}
+
+impl MergeChanges {
+    pub fn is_empty(&self) -> bool {
+        self.insert.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+}
+
+/// Merge the `local` and `remote` frames, which were both changed starting from the `base`
+/// frames, by frame id.
+///
+/// A frame changed on both sides keeps the version which was edited last. A frame deleted on one
+/// side is deleted, unless it was changed on the other side.
+pub fn three_way_merge(
+    base: &[CompletedFrame],
+    local: &[CompletedFrame],
+    remote: &[CompletedFrame],
+) -> MergeChanges {
+    let by_id = |frames: &'_ [CompletedFrame]| -> HashMap<String, CompletedFrame> {
+        frames
+            .iter()
+            .map(|f| (f.frame().id().to_string(), f.clone()))
+            .collect()
+    };
+    let (base, local_by_id) = (by_id(base), by_id(local));
+    let unchanged_since_base = |frame: &CompletedFrame| matches!(base.get(frame.frame().id()), Some(b) if same_content(b, frame));
+
+    let mut changes = MergeChanges::default();
+    let remote_ids: HashSet<&str> = remote.iter().map(|f| f.frame().id()).collect();
+    for remote_frame in remote {
+        match local_by_id.get(remote_frame.frame().id()) {
+            Some(local_frame) => {
+                if !same_content(local_frame, remote_frame)
+                    && remote_frame.frame().last_edit() > local_frame.frame().last_edit()
+                {
+                    changes.update.push(remote_frame.clone());
+                }
+            }
+            // Deleted locally, the deletion wins unless the frame was changed remotely
+            None if unchanged_since_base(remote_frame) => {}
+            None => changes.insert.push(remote_frame.clone()),
+        }
+    }
+    for local_frame in local {
+        // Deleted remotely, the deletion wins unless the frame was changed locally
+        if !remote_ids.contains(local_frame.frame().id()) && unchanged_since_base(local_frame) {
+            changes.delete.push(local_frame.frame().id().to_string());
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::{common::NonEmptyString, frame::Frame};
+
+    fn create_test_frame(id: &str, start_hour: u32, last_edit_hour: u32) -> CompletedFrame {
+        let frame = Frame::new(
+            NonEmptyString::new("project").unwrap().into(),
+            Some(id.to_string()),
+            Some(
+                Local
+                    .with_ymd_and_hms(2025, 1, 1, start_hour, 0, 0)
+                    .unwrap(),
+            ),
+            Some(
+                Local
+                    .with_ymd_and_hms(2025, 1, 1, start_hour + 1, 0, 0)
+                    .unwrap(),
+            ),
+            vec![],
+            Some(
+                Local
+                    .with_ymd_and_hms(2025, 1, 2, last_edit_hour, 0, 0)
+                    .unwrap(),
+            ),
+        );
+        CompletedFrame::from_frame(frame).unwrap()
+    }
+
+    #[test]
+    fn test_merge_frames() {
+        let local = vec![create_test_frame("a", 9, 1), create_test_frame("b", 10, 1)];
+        let other = vec![
+            create_test_frame("a", 9, 5),
+            create_test_frame("b", 11, 5),
+            create_test_frame("c", 12, 5),
+        ];
+
+        let merge = merge_frames(&local, other);
+
+        assert_eq!(merge.unchanged, 1);
+        assert_eq!(merge.new.len(), 1);
+        assert_eq!(merge.new[0].frame().id(), "c");
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].local.frame().id(), "b");
+    }
+
+    #[test]
+    fn test_three_way_merge_keeps_newer_edit() {
+        let base = vec![create_test_frame("a", 9, 1)];
+        let local = vec![create_test_frame("a", 10, 3)];
+        let remote = vec![create_test_frame("a", 11, 5)];
+
+        let changes = three_way_merge(&base, &local, &remote);
+        assert_eq!(changes.update.len(), 1);
+        assert_eq!(changes.update[0].frame().start(), remote[0].frame().start());
+
+        let changes = three_way_merge(&base, &remote, &local);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_three_way_merge_adds_new_frames_of_both_sides() {
+        let base = vec![create_test_frame("a", 9, 1)];
+        let local = vec![create_test_frame("a", 9, 1), create_test_frame("b", 10, 2)];
+        let remote = vec![create_test_frame("a", 9, 1), create_test_frame("c", 11, 2)];
+
+        let changes = three_way_merge(&base, &local, &remote);
+
+        assert_eq!(changes.insert.len(), 1);
+        assert_eq!(changes.insert[0].frame().id(), "c");
+        assert!(changes.update.is_empty());
+        assert!(changes.delete.is_empty());
+    }
+
+    #[test]
+    fn test_three_way_merge_deletions() {
+        let base = vec![create_test_frame("a", 9, 1), create_test_frame("b", 10, 1)];
+        // "a" was deleted remotely, "b" was deleted locally but changed remotely
+        let local = vec![create_test_frame("a", 9, 1)];
+        let remote = vec![create_test_frame("b", 12, 2)];
+
+        let changes = three_way_merge(&base, &local, &remote);
+
+        assert_eq!(changes.delete, vec!["a".to_string()]);
+        assert_eq!(changes.insert.len(), 1);
+        assert_eq!(changes.insert[0].frame().id(), "b");
+    }
+
+    #[test]
+    fn test_conflict_newer() {
+        let local = create_test_frame("a", 9, 3);
+        let other = create_test_frame("a", 10, 2);
+        let conflict = Conflict {
+            local: local.clone(),
+            other: other.clone(),
+        };
+        assert_eq!(conflict.newer().frame().start(), local.frame().start());
+
+        let conflict = Conflict {
+            local: other,
+            other: local.clone(),
+        };
+        assert_eq!(conflict.newer().frame().start(), local.frame().start());
+    }
+
+    #[test]
+    fn test_parse_watson_frames() {
+        let frames = parse_watson_frames(
+            r#"[[1700000000, 1700003600, "project", "abc", ["tag"], 1700003600]]"#,
+        )
+        .unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].frame().id(), "abc");
+        assert!(parse_watson_frames("{}").is_err());
+    }
+}
@@ -0,0 +1,195 @@
+// The tree of hierarchical projects, whose names are separated by `/` like `client/area/task`.
+// The time of a project is rolled up into the totals of its parents.
+//
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use chrono::Duration;
+
+use crate::frame::{CompletedFrame, ProjectName};
+
+/// The separator of the levels in a project name
+pub const PROJECT_SEPARATOR: char = '/';
+
+/// A project in the tree, which may only exist as parent of other projects
+pub struct ProjectNode<'a> {
+    /// The full name, e.g. `client/area`
+    name: String,
+    /// The frames of the project and of all its subprojects
+    frames: Vec<&'a CompletedFrame>,
+    children: BTreeMap<String, ProjectNode<'a>>,
+}
+
+impl<'a> ProjectNode<'a> {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            frames: Vec::new(),
+            children: BTreeMap::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The last level of the name, e.g. `area` for `client/area`
+    pub fn segment(&self) -> &str {
+        self.name
+            .rsplit(PROJECT_SEPARATOR)
+            .next()
+            .unwrap_or(&self.name)
+    }
+
+    pub fn frames(&self) -> &[&'a CompletedFrame] {
+        &self.frames
+    }
+
+    /// The time tracked for the project and all its subprojects
+    pub fn total(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration()).sum()
+    }
+
+    /// The subprojects, the longest first and by name for equal totals
+    pub fn children(&self) -> Vec<&ProjectNode<'a>> {
+        let mut children: Vec<&ProjectNode<'a>> = self.children.values().collect();
+        // The children are ordered by name already, which the stable sort keeps for equal totals
+        children.sort_by_key(|child| std::cmp::Reverse(child.total()));
+        children
+    }
+
+    fn collect_nodes<'b>(&'b self, depth: usize, nodes: &mut Vec<(usize, &'b ProjectNode<'a>)>) {
+        nodes.push((depth, self));
+        for child in self.children() {
+            child.collect_nodes(depth + 1, nodes);
+        }
+    }
+}
+
+/// The projects as a tree, rolling up the time of each project into its parents
+pub struct ProjectTree<'a> {
+    /// The node above the top-level projects, which has no name
+    root: ProjectNode<'a>,
+}
+
+impl<'a> ProjectTree<'a> {
+    /// The tree of the projects, without any tracked time
+    pub fn from_projects<'p>(projects: impl IntoIterator<Item = &'p ProjectName>) -> Self {
+        let mut tree = Self {
+            root: ProjectNode::new(String::new()),
+        };
+        for project in projects {
+            tree.insert(project, None);
+        }
+        tree
+    }
+
+    /// The tree of the projects of the frames with their tracked time
+    pub fn from_frames(frames: impl IntoIterator<Item = &'a CompletedFrame>) -> Self {
+        let mut tree = Self::from_projects([]);
+        for frame in frames {
+            tree.insert(frame.frame().project(), Some(frame));
+        }
+        tree
+    }
+
+    /// Create the nodes of the project and its parents, adding the frame to each of them
+    fn insert(&mut self, project: &ProjectName, frame: Option<&'a CompletedFrame>) {
+        let mut node = &mut self.root;
+        let mut name = String::new();
+        for segment in project.as_str().split(PROJECT_SEPARATOR) {
+            if !name.is_empty() {
+                name.push(PROJECT_SEPARATOR);
+            }
+            name.push_str(segment);
+            node = node
+                .children
+                .entry(segment.to_string())
+                .or_insert_with(|| ProjectNode::new(name.clone()));
+            node.frames.extend(frame);
+        }
+    }
+
+    /// The top-level projects, the longest first
+    pub fn roots(&self) -> Vec<&ProjectNode<'a>> {
+        self.root.children()
+    }
+
+    /// All projects depth-first with their depth, 0 for top-level projects
+    pub fn nodes(&self) -> Vec<(usize, &ProjectNode<'a>)> {
+        let mut nodes = Vec::new();
+        for root in self.roots() {
+            root.collect_nodes(0, &mut nodes);
+        }
+        nodes
+    }
+}
+
+impl Display for ProjectTree<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (depth, node) in self.nodes() {
+            writeln!(f, "{}{}", "  ".repeat(depth), node.segment())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::{common::NonEmptyString, frame::Frame};
+
+    fn project(name: &str) -> ProjectName {
+        NonEmptyString::new(name).unwrap().into()
+    }
+
+    fn create_frame(name: &str, minutes: i64) -> CompletedFrame {
+        let start = Local.with_ymd_and_hms(2025, 1, 6, 9, 0, 0).unwrap();
+        CompletedFrame::from_frame(Frame::new(
+            project(name),
+            None,
+            Some(start),
+            Some(start + Duration::minutes(minutes)),
+            vec![],
+            None,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_totals_roll_up_into_parents() {
+        let frames = [
+            create_frame("client/area/task", 30),
+            create_frame("client/area", 15),
+            create_frame("client/other", 60),
+            create_frame("internal", 20),
+        ];
+        let tree = ProjectTree::from_frames(&frames);
+        let nodes: Vec<(usize, &str, i64)> = tree
+            .nodes()
+            .into_iter()
+            .map(|(depth, node)| (depth, node.name(), node.total().num_minutes()))
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![
+                (0, "client", 105),
+                (1, "client/other", 60),
+                (1, "client/area", 45),
+                (2, "client/area/task", 30),
+                (0, "internal", 20),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display_project_names_as_tree() {
+        let projects = [project("b/y"), project("a"), project("b/x/1")];
+        assert_eq!(
+            ProjectTree::from_projects(&projects).to_string(),
+            "a\nb\n  x\n    1\n  y\n"
+        );
+    }
+}
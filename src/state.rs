@@ -1,14 +1,16 @@
+use std::fmt::Display;
+
 use chrono::{DateTime, Local};
 
 use crate::{
     common::NonEmptyString,
-    frame::{CompletedFrame, Frame, FrameEdit, ProjectName},
+    frame::{CompletedFrame, Frame, ProjectName, ValidFrameEdit},
 };
 
 /// The backend to store the state (i.e. ongoing frames)
 /// This needs to be implemented by specific storage. To actually save and load state, use `StateStore`.
 pub trait StateStoreBackend {
-    type StateStoreBackendError;
+    type StateStoreBackendError: Display;
     /// Get the currently ongoing frame, if there is one. Returns None if there is none
     fn get(&self) -> Result<Option<OngoingFrame>, Self::StateStoreBackendError>;
     /// Store an ongoing frame. Overwrites an existing ongoing frame.
@@ -18,10 +20,16 @@ pub trait StateStoreBackend {
 }
 
 pub trait TrackingState {}
+/// Marker for the states which have a current frame, i.e. `Ongoing` and `Paused`
+pub trait WithFrame: TrackingState {}
 pub struct Ongoing {}
+pub struct Paused {}
 pub struct Stopped {}
 impl TrackingState for Ongoing {}
+impl TrackingState for Paused {}
 impl TrackingState for Stopped {}
+impl WithFrame for Ongoing {}
+impl WithFrame for Paused {}
 
 // The state store is the wrapper around the StateStoreBackend to protect from invalid access.
 // Type state pattern is used to enable and disable methods of the StateStore based on whether there is an
@@ -41,25 +49,27 @@ impl<'a, S: StateStoreBackend, T: TrackingState> StateStore<'a, S, T> {
 }
 
 pub struct FrameStopped<'a, S: StateStoreBackend> {
-    pub frame: CompletedFrame,
+    /// One frame per segment in which the frame was not paused
+    pub frames: Vec<CompletedFrame>,
     #[allow(dead_code)]
     // For API completness we want to include the store here even though it is currently unused.
     pub store: StateStore<'a, S, Stopped>,
 }
 
-impl<'a, S> StateStore<'a, S, Ongoing>
+impl<'a, S, T> StateStore<'a, S, T>
 where
     S: StateStoreBackend,
+    T: WithFrame,
 {
+    /// Stop the current frame. If it is paused, it ends when the pause started.
     pub fn stop(
         self,
         at: &DateTime<Local>,
     ) -> Result<FrameStopped<'a, S>, S::StateStoreBackendError> {
-        let frame = Frame::from(self.get_ongoing()?);
-        let completed_frame = frame.set_end(*at);
+        let frames = self.get_ongoing()?.complete(*at);
         self.backend.clear()?;
         Ok(FrameStopped {
-            frame: completed_frame,
+            frames,
             store: StateStore::new(self.backend),
         })
     }
@@ -85,6 +95,54 @@ where
     }
 }
 
+pub struct FramePaused<'a, S: StateStoreBackend> {
+    pub frame: OngoingFrame,
+    #[allow(dead_code)]
+    // For API completness we want to include the store here even though it is currently unused.
+    pub store: StateStore<'a, S, Paused>,
+}
+
+impl<'a, S> StateStore<'a, S, Ongoing>
+where
+    S: StateStoreBackend,
+{
+    pub fn pause(
+        self,
+        at: DateTime<Local>,
+    ) -> Result<FramePaused<'a, S>, S::StateStoreBackendError> {
+        let mut ongoing_frame = self.get_ongoing()?;
+        ongoing_frame.pauses.push(Pause {
+            start: at,
+            end: None,
+        });
+        self.backend.store(&ongoing_frame)?;
+        Ok(FramePaused {
+            frame: ongoing_frame,
+            store: StateStore::new(self.backend),
+        })
+    }
+}
+
+impl<'a, S> StateStore<'a, S, Paused>
+where
+    S: StateStoreBackend,
+{
+    pub fn resume(
+        self,
+        at: DateTime<Local>,
+    ) -> Result<FrameStarted<'a, S>, S::StateStoreBackendError> {
+        let mut ongoing_frame = self.get_ongoing()?;
+        if let Some(pause) = ongoing_frame.pauses.last_mut() {
+            pause.end = Some(at);
+        }
+        self.backend.store(&ongoing_frame)?;
+        Ok(FrameStarted {
+            frame: ongoing_frame,
+            store: StateStore::new(self.backend),
+        })
+    }
+}
+
 pub struct FrameStarted<'a, S: StateStoreBackend> {
     pub frame: OngoingFrame,
     #[allow(dead_code)]
@@ -113,20 +171,40 @@ where
 
 pub enum StateStoreVariant<'a, S: StateStoreBackend> {
     Ongoing(StateStore<'a, S, Ongoing>),
+    Paused(StateStore<'a, S, Paused>),
     Stopped(StateStore<'a, S, Stopped>),
 }
 
+impl<S: StateStoreBackend> StateStoreVariant<'_, S> {
+    /// The current frame, if the state is ongoing or paused
+    pub fn get_ongoing(&self) -> Result<Option<OngoingFrame>, S::StateStoreBackendError> {
+        match self {
+            StateStoreVariant::Ongoing(state_store) => state_store.get_ongoing().map(Some),
+            StateStoreVariant::Paused(state_store) => state_store.get_ongoing().map(Some),
+            StateStoreVariant::Stopped(_) => Ok(None),
+        }
+    }
+}
+
 /// Getter for a StateStore, based on a backend.
 /// Use this method to get the StateStore in the currently active state.
 pub fn get_state_store<'a, S: StateStoreBackend>(
     backend: &'a S,
 ) -> Result<StateStoreVariant<'a, S>, S::StateStoreBackendError> {
     match backend.get()? {
+        Some(frame) if frame.is_paused() => Ok(StateStoreVariant::Paused(StateStore::new(backend))),
         Some(_) => Ok(StateStoreVariant::Ongoing(StateStore::new(backend))),
         None => Ok(StateStoreVariant::Stopped(StateStore::new(backend))),
     }
 }
 
+#[derive(Debug, Clone)]
+/// An interruption of the ongoing frame. The pause is still running if it has no end.
+pub struct Pause {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+}
+
 #[derive(Debug, Clone)]
 ///Representation of a currently ongoing frame
 /// The frame is not completed and the storing of this is delegated to the StateStoreBackend
@@ -134,17 +212,90 @@ pub struct OngoingFrame {
     project: ProjectName,
     start: DateTime<Local>,
     tags: Vec<NonEmptyString>,
+    pauses: Vec<Pause>,
 }
 
 impl OngoingFrame {
     pub fn new(project: ProjectName, start: DateTime<Local>, tags: Vec<NonEmptyString>) -> Self {
+        Self::with_pauses(project, start, tags, Vec::new())
+    }
+
+    pub fn with_pauses(
+        project: ProjectName,
+        start: DateTime<Local>,
+        tags: Vec<NonEmptyString>,
+        pauses: Vec<Pause>,
+    ) -> Self {
         Self {
             project,
             start,
             tags,
+            pauses,
         }
     }
 
+    pub fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+
+    /// Whether the last pause is still running
+    pub fn is_paused(&self) -> bool {
+        self.pauses.last().is_some_and(|pause| pause.end.is_none())
+    }
+
+    /// Whether the frame can be stopped at `at`, i.e. `at` is neither before the start nor within
+    /// a pause before which nothing was tracked. Stopping there would leave no frame to save.
+    pub fn can_stop_at(&self, at: DateTime<Local>) -> bool {
+        let in_pause = self
+            .pauses
+            .iter()
+            .any(|pause| pause.start <= at && pause.end.is_none_or(|end| at < end));
+        at >= self.start && !(in_pause && self.segments(at).is_empty())
+    }
+
+    /// The segments between the pauses as (start, end) pairs, if the frame ends at `end`.
+    /// Segments shorter than a second, which is the precision of the stored frames, are left out,
+    /// unless the frame was never paused.
+    pub fn segments(&self, end: DateTime<Local>) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        if self.pauses.is_empty() {
+            return vec![(self.start, end)];
+        }
+        let mut segments = Vec::new();
+        let mut segment_start = self.start;
+        for pause in &self.pauses {
+            let segment_end = pause.start.min(end);
+            if (segment_end - segment_start).num_seconds() > 0 {
+                segments.push((segment_start, segment_end));
+            }
+            match pause.end {
+                Some(pause_end) => segment_start = segment_start.max(pause_end),
+                None => return segments,
+            }
+        }
+        if (end - segment_start).num_seconds() > 0 {
+            segments.push((segment_start, end));
+        }
+        segments
+    }
+
+    /// Complete the frame at `end` into one frame per segment, see `segments`
+    pub fn complete(&self, end: DateTime<Local>) -> Vec<CompletedFrame> {
+        self.segments(end)
+            .into_iter()
+            .map(|(start, end)| {
+                Frame::new(
+                    self.project.clone(),
+                    None,
+                    Some(start),
+                    None,
+                    self.tags.clone(),
+                    None,
+                )
+                .set_end(end)
+            })
+            .collect()
+    }
+
     pub fn project(&self) -> &ProjectName {
         &self.project
     }
@@ -157,9 +308,106 @@ impl OngoingFrame {
         &self.tags
     }
 
-    pub fn update_from(&mut self, edit: FrameEdit) {
+    pub fn update_from(&mut self, edit: ValidFrameEdit) {
         self.project = edit.project().clone();
         self.start = edit.start();
         self.tags = Vec::from(edit.tags());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 1, 1, hour, minute, 0).unwrap()
+    }
+
+    fn ongoing_frame(pauses: Vec<Pause>) -> OngoingFrame {
+        OngoingFrame::with_pauses(
+            NonEmptyString::new("project").unwrap().into(),
+            at(9, 0),
+            vec![NonEmptyString::new("tag").unwrap()],
+            pauses,
+        )
+    }
+
+    #[test]
+    fn test_segments_without_pauses() {
+        assert_eq!(
+            ongoing_frame(vec![]).segments(at(10, 0)),
+            vec![(at(9, 0), at(10, 0))]
+        );
+    }
+
+    #[test]
+    fn test_can_stop_at() {
+        let frame = ongoing_frame(vec![
+            Pause {
+                start: at(9, 0),
+                end: Some(at(9, 30)),
+            },
+            Pause {
+                start: at(10, 0),
+                end: None,
+            },
+        ]);
+        assert!(!frame.can_stop_at(at(8, 59)));
+        assert!(!frame.can_stop_at(at(9, 10)));
+        assert!(frame.can_stop_at(at(9, 40)));
+        assert!(frame.can_stop_at(at(11, 0)));
+    }
+
+    #[test]
+    fn test_segments_with_pauses() {
+        let frame = ongoing_frame(vec![
+            Pause {
+                start: at(9, 30),
+                end: Some(at(9, 45)),
+            },
+            Pause {
+                start: at(10, 0),
+                end: Some(at(11, 0)),
+            },
+        ]);
+        assert_eq!(
+            frame.segments(at(12, 0)),
+            vec![
+                (at(9, 0), at(9, 30)),
+                (at(9, 45), at(10, 0)),
+                (at(11, 0), at(12, 0))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_segments_end_when_paused() {
+        let frame = ongoing_frame(vec![Pause {
+            start: at(9, 30),
+            end: None,
+        }]);
+        assert!(frame.is_paused());
+        assert_eq!(frame.segments(at(12, 0)), vec![(at(9, 0), at(9, 30))]);
+    }
+
+    #[test]
+    fn test_complete_keeps_project_and_tags() {
+        let frame = ongoing_frame(vec![Pause {
+            start: at(9, 30),
+            end: Some(at(10, 0)),
+        }]);
+        let frames = frame.complete(at(11, 0));
+        assert_eq!(frames.len(), 2);
+        assert_ne!(frames[0].frame().id(), frames[1].frame().id());
+        for completed in &frames {
+            assert_eq!(completed.frame().project(), frame.project());
+            assert_eq!(completed.frame().tags()[0].as_str(), "tag");
+        }
+        assert_eq!(
+            frames.iter().map(|f| f.duration()).sum::<Duration>(),
+            Duration::minutes(90)
+        );
+    }
+}
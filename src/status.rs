@@ -0,0 +1,307 @@
+// The status of the current frame, as shown by `watsup status` in the formats for prompts and
+// status bars
+//
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    frame::CompletedFrame,
+    log::{beginning_of_day, beginning_of_week, format_duration},
+    state::OngoingFrame,
+};
+
+/// The status of the current frame at a point in time. In JSON the durations are in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    pub project: String,
+    pub tags: Vec<String>,
+    pub start: DateTime<Local>,
+    /// The tracked time of the current frame, without its pauses
+    #[serde(with = "seconds")]
+    pub elapsed: Duration,
+    /// The time tracked today, including the current frame
+    #[serde(with = "seconds")]
+    pub today: Duration,
+    /// The time tracked for the project of the current frame this week, including the frame
+    #[serde(with = "seconds")]
+    pub week: Duration,
+    pub paused: bool,
+    /// The start of the pause if the frame is paused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused_since: Option<DateTime<Local>>,
+}
+
+/// (De)serialization of durations as whole seconds
+mod seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_seconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        i64::deserialize(deserializer).map(Duration::seconds)
+    }
+}
+
+/// The time of the segments which was tracked after `since`
+fn tracked_since(
+    segments: &[(DateTime<Local>, DateTime<Local>)],
+    since: DateTime<Local>,
+) -> Duration {
+    segments
+        .iter()
+        .filter(|(_, end)| *end > since)
+        .map(|(start, end)| *end - (*start).max(since))
+        .sum()
+}
+
+impl Status {
+    /// The status of the ongoing frame at `now`. `frames` are the completed frames of this week.
+    pub fn new(ongoing: &OngoingFrame, frames: &[CompletedFrame], now: DateTime<Local>) -> Self {
+        let segments = ongoing.segments(now);
+        let elapsed = segments.iter().map(|(start, end)| *end - *start).sum();
+        // Only the part of the current frame after midnight was tracked today
+        let today_start = beginning_of_day(now);
+        let today: Duration = frames
+            .iter()
+            .filter(|f| *f.frame().start() >= today_start)
+            .map(|f| f.duration())
+            .sum();
+        let week: Duration = frames
+            .iter()
+            .filter(|f| f.frame().project() == ongoing.project())
+            .map(|f| f.duration())
+            .sum();
+        Self {
+            project: ongoing.project().as_str().to_string(),
+            tags: ongoing
+                .tags()
+                .iter()
+                .map(|tag| tag.as_str().to_string())
+                .collect(),
+            start: *ongoing.start(),
+            elapsed,
+            today: today + tracked_since(&segments, today_start),
+            week: week + tracked_since(&segments, beginning_of_week(now)),
+            paused: ongoing.is_paused(),
+            paused_since: ongoing
+                .pauses()
+                .last()
+                .filter(|_| ongoing.is_paused())
+                .map(|pause| pause.start),
+        }
+    }
+}
+
+/// The values which can be used in a status template, written as `{name}`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placeholder {
+    Project,
+    /// The tags, separated by comma
+    Tags,
+    /// The elapsed time as `H:MM:SS`
+    Elapsed,
+    /// The start time as `HH:MM`
+    Start,
+    /// The time tracked today as `H:MM:SS`
+    Today,
+    /// The time tracked for the project this week as `H:MM:SS`
+    Week,
+}
+
+impl Placeholder {
+    const ALL: [(&'static str, Placeholder); 6] = [
+        ("project", Placeholder::Project),
+        ("tags", Placeholder::Tags),
+        ("elapsed", Placeholder::Elapsed),
+        ("start", Placeholder::Start),
+        ("today", Placeholder::Today),
+        ("week", Placeholder::Week),
+    ];
+
+    fn value(&self, status: &Status) -> String {
+        match self {
+            Placeholder::Project => status.project.clone(),
+            Placeholder::Tags => status.tags.join(", "),
+            Placeholder::Elapsed => format_duration(status.elapsed),
+            Placeholder::Start => status.start.format("%H:%M").to_string(),
+            Placeholder::Today => format_duration(status.today),
+            Placeholder::Week => format_duration(status.week),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// A template for the status like `{project} {elapsed}`. Braces are written as `{{` and `}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusTemplate(Vec<TemplatePart>);
+
+impl StatusTemplate {
+    /// A template showing only the placeholder
+    pub fn placeholder(placeholder: Placeholder) -> Self {
+        Self(vec![TemplatePart::Placeholder(placeholder)])
+    }
+
+    pub fn render(&self, status: &Status) -> String {
+        self.0
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Placeholder(placeholder) => placeholder.value(status),
+            })
+            .collect()
+    }
+}
+
+/// Parse a status template, failing on unknown placeholders and unmatched braces
+pub fn parse_template(template: &str) -> Result<StatusTemplate, String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err("Unmatched { in template, write {{ for a brace".into()),
+                    }
+                }
+                let placeholder = Placeholder::ALL
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, p)| *p)
+                    .ok_or_else(|| {
+                        let names: Vec<&str> = Placeholder::ALL.iter().map(|(n, _)| *n).collect();
+                        format!(
+                            "Unknown placeholder {{{}}}, expected one of {}",
+                            name,
+                            names.join(", ")
+                        )
+                    })?;
+                if !text.is_empty() {
+                    parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(TemplatePart::Placeholder(placeholder));
+            }
+            '}' => return Err("Unmatched } in template, write }} for a brace".to_string()),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+    Ok(StatusTemplate(parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{common::NonEmptyString, frame::Frame};
+
+    fn create_status() -> Status {
+        let now = Local.with_ymd_and_hms(2025, 1, 8, 12, 0, 0).unwrap();
+        let ongoing = OngoingFrame::new(
+            NonEmptyString::new("project").unwrap().into(),
+            now - Duration::minutes(90),
+            vec![
+                NonEmptyString::new("a").unwrap(),
+                NonEmptyString::new("b").unwrap(),
+            ],
+        );
+        let create_frame = |project: &str, start: DateTime<Local>| {
+            CompletedFrame::from_frame(Frame::new(
+                NonEmptyString::new(project).unwrap().into(),
+                None,
+                Some(start),
+                Some(start + Duration::hours(1)),
+                vec![],
+                None,
+            ))
+            .unwrap()
+        };
+        let frames = [
+            create_frame("project", now - Duration::days(1)),
+            create_frame("other", now - Duration::days(1)),
+            create_frame("other", now - Duration::hours(4)),
+        ];
+        Status::new(&ongoing, &frames, now)
+    }
+
+    #[test]
+    fn test_status_totals() {
+        let status = create_status();
+        assert_eq!(status.elapsed, Duration::minutes(90));
+        assert_eq!(status.today, Duration::minutes(150));
+        assert_eq!(status.week, Duration::minutes(150));
+        assert!(!status.paused);
+    }
+
+    #[test]
+    fn test_frame_since_yesterday_counts_today_from_midnight() {
+        let now = Local.with_ymd_and_hms(2025, 1, 8, 1, 0, 0).unwrap();
+        let ongoing = OngoingFrame::new(
+            NonEmptyString::new("project").unwrap().into(),
+            now - Duration::hours(2),
+            vec![],
+        );
+        let status = Status::new(&ongoing, &[], now);
+        assert_eq!(status.elapsed, Duration::hours(2));
+        assert_eq!(status.today, Duration::hours(1));
+        assert_eq!(status.week, Duration::hours(2));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let template =
+            parse_template("{{{project}}} [{tags}] {elapsed} since {start}, {today}, {week}")
+                .unwrap();
+        assert_eq!(
+            template.render(&create_status()),
+            "{project} [a, b] 1:30:00 since 10:30, 2:30:00, 2:30:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_template() {
+        assert!(
+            parse_template("{unknown}")
+                .unwrap_err()
+                .starts_with("Unknown placeholder {unknown}")
+        );
+        assert!(parse_template("a } b").is_err());
+        assert!(parse_template("{project").is_err());
+    }
+
+    #[test]
+    fn test_status_json() {
+        let status = create_status();
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["project"], "project");
+        assert_eq!(json["tags"][1], "b");
+        assert_eq!(json["elapsed"], 5400);
+        assert_eq!(json["today"], 9000);
+        assert_eq!(json["week"], 9000);
+        assert!(json.get("paused_since").is_none());
+        assert_eq!(serde_json::from_value::<Status>(json).unwrap(), status);
+    }
+}
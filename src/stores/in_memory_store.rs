@@ -1,5 +1,5 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use chrono::{DateTime, Local};
 
@@ -44,9 +44,32 @@ impl std::fmt::Display for InMemoryStoreError {
 impl FrameStore for InMemoryStore {
     type FrameStoreError = InMemoryStoreError;
 
-    fn save_frame(&self, frame: &CompletedFrame) -> Result<(), Self::FrameStoreError> {
+    fn update_frames(
+        &self,
+        insert: &[CompletedFrame],
+        update: &[CompletedFrame],
+        delete: &[String],
+    ) -> Result<(), Self::FrameStoreError> {
         let mut frames = self.frames.borrow_mut();
-        frames.insert(frame.frame().id().to_string(), frame.clone());
+        let mut inserted_ids = HashSet::new();
+        for frame in insert {
+            let id = frame.frame().id();
+            if frames.contains_key(id) || !inserted_ids.insert(id) {
+                return Err(InMemoryStoreError::Generic(format!("Duplicate id {}", id)));
+            }
+        }
+        if let Some(frame) = update.iter().find(|f| !frames.contains_key(f.frame().id())) {
+            return Err(InMemoryStoreError::Generic(format!(
+                "Unknown id {}",
+                frame.frame().id()
+            )));
+        }
+        for id in delete {
+            frames.remove(id);
+        }
+        for frame in insert.iter().chain(update) {
+            frames.insert(frame.frame().id().to_string(), frame.clone());
+        }
         Ok(())
     }
 
@@ -71,6 +94,12 @@ impl FrameStore for InMemoryStore {
         Ok(frames.get(frame_id).cloned())
     }
 
+    fn get_all_frames(&self) -> Result<Vec<CompletedFrame>, Self::FrameStoreError> {
+        let mut frames: Vec<CompletedFrame> = self.frames.borrow().values().cloned().collect();
+        frames.sort();
+        Ok(frames)
+    }
+
     fn get_frames(
         &self,
         start: DateTime<Local>,
@@ -83,7 +112,7 @@ impl FrameStore for InMemoryStore {
                 let frame_start = frame.frame().start();
                 let frame_end = frame.end();
                 // Include frames that overlap with the requested time range
-                frame_start < &end && &frame_end > &start
+                frame_start < &end && frame_end > start
             })
             .cloned()
             .collect();
@@ -150,7 +179,7 @@ mod tests {
         let frame = create_test_frame(project, 9, 10);
         let frame_id = frame.frame().id().to_string();
 
-        store.save_frame(&frame).unwrap();
+        store.insert_frame(&frame).unwrap();
 
         let retrieved = store.get_frame(&frame_id).unwrap();
         assert!(retrieved.is_some());
@@ -166,9 +195,9 @@ mod tests {
         let frame2 = create_test_frame(project.clone(), 11, 12);
         let frame3 = create_test_frame(project, 10, 11);
 
-        store.save_frame(&frame1).unwrap();
-        store.save_frame(&frame2).unwrap();
-        store.save_frame(&frame3).unwrap();
+        store.insert_frame(&frame1).unwrap();
+        store.insert_frame(&frame2).unwrap();
+        store.insert_frame(&frame3).unwrap();
 
         let last = store.get_last_frame().unwrap();
         assert_eq!(last.frame().id(), frame2.frame().id());
@@ -184,9 +213,9 @@ mod tests {
         let frame2 = create_test_frame(project2.clone(), 10, 11);
         let frame3 = create_test_frame(project1.clone(), 11, 12);
 
-        store.save_frame(&frame1).unwrap();
-        store.save_frame(&frame2).unwrap();
-        store.save_frame(&frame3).unwrap();
+        store.insert_frame(&frame1).unwrap();
+        store.insert_frame(&frame2).unwrap();
+        store.insert_frame(&frame3).unwrap();
 
         let projects = store.get_projects().unwrap();
         assert_eq!(projects.len(), 2);
@@ -204,10 +233,10 @@ mod tests {
         let frame3 = create_test_frame(project.clone(), 10, 11); // In range
         let frame4 = create_test_frame(project, 12, 13); // Outside range
 
-        store.save_frame(&frame1).unwrap();
-        store.save_frame(&frame2).unwrap();
-        store.save_frame(&frame3).unwrap();
-        store.save_frame(&frame4).unwrap();
+        store.insert_frame(&frame1).unwrap();
+        store.insert_frame(&frame2).unwrap();
+        store.insert_frame(&frame3).unwrap();
+        store.insert_frame(&frame4).unwrap();
 
         let start = Local.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
         let end = Local.with_ymd_and_hms(2025, 1, 1, 11, 30, 0).unwrap();
@@ -258,7 +287,7 @@ mod tests {
         let frame = create_test_frame(project.clone(), 9, 10);
         let frame_id = frame.frame().id().to_string();
 
-        store.save_frame(&frame).unwrap();
+        store.insert_frame(&frame).unwrap();
 
         // Create a new frame with the same ID but different times
         let start = Local.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
@@ -273,9 +302,19 @@ mod tests {
         );
         let updated_frame = CompletedFrame::from_frame(updated_frame).unwrap();
 
-        store.save_frame(&updated_frame).unwrap();
+        store.update_frame(&updated_frame).unwrap();
 
         let retrieved = store.get_frame(&frame_id).unwrap().unwrap();
         assert_eq!(retrieved.end().hour(), 12);
     }
+
+    #[test]
+    fn test_insert_existing_frame_fails() {
+        let store = InMemoryStore::new();
+        let frame = create_test_frame(create_test_project(), 9, 10);
+
+        store.insert_frame(&frame).unwrap();
+
+        assert!(store.insert_frame(&frame).is_err());
+    }
 }
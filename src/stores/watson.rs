@@ -1,7 +1,9 @@
 // The compatiblity layer to watson (https://github.com/jazzband/Watson/)
 //
 
-use std::{collections::HashSet, fmt::Display, fs::File, io::Read};
+use std::{
+    cell::RefCell, collections::HashSet, fmt::Display, fs::File, io::Read, time::SystemTime,
+};
 
 use chrono::{DateTime, Local, TimeZone};
 use serde::{Deserialize, Serialize, ser::SerializeSeq};
@@ -11,9 +13,10 @@ use crate::{
     common::NonEmptyString,
     config::Config,
     frame::{self, CompletedFrame, FrameStore, ProjectName},
-    state::{OngoingFrame as WatsupOngoingFrame, StateStoreBackend},
+    state::{self, OngoingFrame as WatsupOngoingFrame, StateStoreBackend},
 };
 
+/// A frame as stored in watson's frames file
 #[derive(Clone)]
 pub struct Frame {
     start_timestamp: i64,
@@ -29,15 +32,41 @@ pub struct OngoingFrame {
     project: ProjectName,
     start: i64,
     tags: Vec<NonEmptyString>,
+    /// Not part of watson's state, which ignores unknown keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pauses: Vec<Pause>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Pause {
+    start: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<i64>,
+}
+
+fn local_datetime(timestamp: i64) -> DateTime<Local> {
+    chrono::Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .expect("Invalid timestamp in state")
 }
 
 impl From<OngoingFrame> for WatsupOngoingFrame {
     fn from(ongoing_frame: OngoingFrame) -> Self {
-        let start = chrono::Local
-            .timestamp_opt(ongoing_frame.start, 0)
-            .single()
-            .expect("Invalid timestamp for OngoingFrame::start");
-        WatsupOngoingFrame::new(ongoing_frame.project, start, ongoing_frame.tags)
+        let pauses = ongoing_frame
+            .pauses
+            .iter()
+            .map(|pause| state::Pause {
+                start: local_datetime(pause.start),
+                end: pause.end.map(local_datetime),
+            })
+            .collect();
+        WatsupOngoingFrame::with_pauses(
+            ongoing_frame.project,
+            local_datetime(ongoing_frame.start),
+            ongoing_frame.tags,
+            pauses,
+        )
     }
 }
 
@@ -47,6 +76,14 @@ impl From<&WatsupOngoingFrame> for OngoingFrame {
             project: value.project().clone(),
             start: value.start().timestamp(),
             tags: value.tags().to_vec(),
+            pauses: value
+                .pauses()
+                .iter()
+                .map(|pause| Pause {
+                    start: pause.start.timestamp(),
+                    end: pause.end.map(|end| end.timestamp()),
+                })
+                .collect(),
         }
     }
 }
@@ -228,9 +265,12 @@ mod state_serializaton_tests {
             project: NonEmptyString::new("Project").unwrap().into(),
             start: Local::now().timestamp(),
             tags: vec![],
+            pauses: vec![],
         };
 
         let json = serde_json::to_string(&ongoing_frame).unwrap();
+        // Without pauses, the state is identical to the one of watson
+        assert!(!json.contains("pauses"));
 
         let roundtrip_ongoing_frame: OngoingFrame = serde_json::from_str(&json).unwrap();
 
@@ -238,12 +278,30 @@ mod state_serializaton_tests {
         assert_eq!(ongoing_frame.start, roundtrip_ongoing_frame.start);
         assert_eq!(ongoing_frame.tags, roundtrip_ongoing_frame.tags);
     }
+
+    #[test]
+    fn test_state_with_pauses_roundtrip() {
+        let json = r#"{"project": "Project", "start": 1700000000, "tags": [], "pauses": [{"start": 1700000100, "end": 1700000200}, {"start": 1700000300}]}"#;
+
+        let ongoing_frame: OngoingFrame = serde_json::from_str(json).unwrap();
+        let ongoing_frame = crate::state::OngoingFrame::from(ongoing_frame);
+
+        assert_eq!(ongoing_frame.pauses().len(), 2);
+        assert!(ongoing_frame.is_paused());
+        let serialized = serde_json::to_value(OngoingFrame::from(&ongoing_frame)).unwrap();
+        assert_eq!(
+            serialized["pauses"][1],
+            serde_json::json!({"start": 1700000300})
+        );
+    }
 }
 
 #[derive(Debug)]
 pub enum StoreError {
     Serialization(serde_json::Error),
     IO(std::io::Error),
+    DuplicateId(String),
+    UnknownId(String),
 }
 
 impl Display for StoreError {
@@ -251,6 +309,8 @@ impl Display for StoreError {
         match self {
             StoreError::Serialization(e) => write!(f, "Serialization error: {}", e),
             StoreError::IO(e) => write!(f, "IO error: {}", e),
+            StoreError::DuplicateId(id) => write!(f, "A frame with id {} already exists", id),
+            StoreError::UnknownId(id) => write!(f, "No frame with id {} exists", id),
         }
     }
 }
@@ -267,13 +327,35 @@ impl From<serde_json::Error> for StoreError {
     }
 }
 
+/// The frames of the frames file, kept in memory together with the modification time and the
+/// size of the file to notice changes
+struct FramesCache {
+    modified: SystemTime,
+    len: u64,
+    frames: Vec<CompletedFrame>,
+}
+
 pub struct Store {
     config: Config,
+    /// The cache of the frames, only used by long running processes like the daemon
+    cache: Option<RefCell<Option<FramesCache>>>,
 }
 
 impl Store {
     pub fn new(config: Config) -> Self {
-        Self { config }
+        Self {
+            config,
+            cache: None,
+        }
+    }
+
+    /// A store which keeps the frames in memory. The frames file is only read again after it was
+    /// modified, e.g. by another watsup process.
+    pub fn with_cache(config: Config) -> Self {
+        Self {
+            config,
+            cache: Some(RefCell::new(None)),
+        }
     }
 
     /// Load the frames from the json file stored in the location from the config.
@@ -282,10 +364,26 @@ impl Store {
         if !frames_file_path.exists() {
             return Ok(Vec::new());
         }
+        let metadata = std::fs::metadata(&frames_file_path)?;
+        let (modified, len) = (metadata.modified()?, metadata.len());
+        if let Some(cache) = &self.cache
+            && let Some(cached) = cache.borrow().as_ref()
+            && cached.modified == modified
+            && cached.len == len
+        {
+            return Ok(cached.frames.clone());
+        }
 
         let json = std::fs::read_to_string(frames_file_path)?;
         let frames: Vec<Frame> = serde_json::from_str(&json)?;
-        let frames = frames.into_iter().map(CompletedFrame::from).collect();
+        let frames: Vec<CompletedFrame> = frames.into_iter().map(CompletedFrame::from).collect();
+        if let Some(cache) = &self.cache {
+            *cache.borrow_mut() = Some(FramesCache {
+                modified,
+                len,
+                frames: frames.clone(),
+            });
+        }
         Ok(frames)
     }
 
@@ -299,6 +397,9 @@ impl Store {
         log::debug!("Writing to frames store. frame_count={}", frames.len());
         let json = serde_json::to_string_pretty(&json_array)?;
         std::fs::write(self.config.get_frames_path(), json)?;
+        if let Some(cache) = &self.cache {
+            cache.borrow_mut().take();
+        }
         Ok(())
     }
 }
@@ -306,13 +407,28 @@ impl Store {
 impl FrameStore for Store {
     type FrameStoreError = StoreError;
 
-    fn save_frame(
+    fn update_frames(
         &self,
-        completed_frame: &frame::CompletedFrame,
+        insert: &[CompletedFrame],
+        update: &[CompletedFrame],
+        delete: &[String],
     ) -> Result<(), Self::FrameStoreError> {
         let mut frames = self.load()?;
-        frames.retain(|f| f.frame().id() != completed_frame.frame().id());
-        frames.push(completed_frame.clone());
+        let mut ids: HashSet<String> = frames.iter().map(|f| f.frame().id().to_string()).collect();
+        for frame in insert {
+            if !ids.insert(frame.frame().id().to_string()) {
+                return Err(StoreError::DuplicateId(frame.frame().id().to_string()));
+            }
+        }
+        if let Some(frame) = update.iter().find(|f| !ids.contains(f.frame().id())) {
+            return Err(StoreError::UnknownId(frame.frame().id().to_string()));
+        }
+        frames.retain(|f| {
+            let id = f.frame().id();
+            !delete.iter().any(|d| d == id) && !update.iter().any(|u| u.frame().id() == id)
+        });
+        frames.extend_from_slice(insert);
+        frames.extend_from_slice(update);
         frames.sort();
         self.save(frames)
     }
@@ -343,6 +459,12 @@ impl FrameStore for Store {
             .cloned())
     }
 
+    fn get_all_frames(&self) -> Result<Vec<CompletedFrame>, Self::FrameStoreError> {
+        let mut frames = self.load()?;
+        frames.sort();
+        Ok(frames)
+    }
+
     fn get_frames(
         &self,
         start: DateTime<Local>,
@@ -411,7 +533,7 @@ mod store_tests {
 
         TestConfig {
             config: Config::new(tmp_dir.path().into()),
-            tmp_dir: tmp_dir,
+            tmp_dir,
         }
     }
 
@@ -454,7 +576,7 @@ mod store_tests {
         let test_frame = get_completed_test_frame();
         assert!(store.get_last_frame().is_none());
         store
-            .save_frame(&test_frame)
+            .insert_frame(&test_frame)
             .expect("Saving test frame failed");
 
         assert!(store.get_last_frame().is_some());
@@ -517,7 +639,7 @@ mod store_tests {
         let frame = get_completed_test_frame();
 
         let project = frame.frame().project().clone();
-        store.save_frame(&frame).expect("Failed to save frame");
+        store.insert_frame(&frame).expect("Failed to save frame");
 
         let projects = store.get_projects().expect("Failed to get projects");
 
@@ -533,12 +655,102 @@ mod store_tests {
         let frame1 = get_completed_test_frame_with_project(project.clone());
         let frame2 = get_completed_test_frame_with_project(project.clone());
 
-        store.save_frame(&frame1).expect("Failed to save frame");
-        store.save_frame(&frame2).expect("Failed to save frame");
+        store.insert_frame(&frame1).expect("Failed to save frame");
+        store.insert_frame(&frame2).expect("Failed to save frame");
 
         let projects = store.get_projects().expect("Failed to get projects");
 
         assert_eq!(projects.len(), 1);
         assert_eq!(projects[0], project);
     }
+
+    #[test]
+    fn test_update_frames_saves_and_deletes() {
+        let test_config = get_test_config();
+        let store = Store::new(test_config.config);
+        let kept = get_completed_test_frame();
+        let deleted =
+            get_completed_test_frame_with_project(NonEmptyString::new("deleted").unwrap().into());
+        store.insert_frame(&kept).expect("Failed to save frame");
+        store.insert_frame(&deleted).expect("Failed to save frame");
+
+        let added =
+            get_completed_test_frame_with_project(NonEmptyString::new("added").unwrap().into());
+        store
+            .update_frames(
+                std::slice::from_ref(&added),
+                &[],
+                &[deleted.frame().id().to_string()],
+            )
+            .expect("Failed to update frames");
+
+        assert!(store.get_frame(kept.frame().id()).unwrap().is_some());
+        assert!(store.get_frame(added.frame().id()).unwrap().is_some());
+        assert!(store.get_frame(deleted.frame().id()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_insert_frame_with_existing_id_fails() {
+        let test_config = get_test_config();
+        let store = Store::new(test_config.config);
+        let frame = get_completed_test_frame();
+        store.insert_frame(&frame).expect("Failed to save frame");
+
+        let result = store.insert_frame(&frame);
+
+        assert!(matches!(result, Err(StoreError::DuplicateId(_))));
+        assert_eq!(store.get_all_frames().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_update_unknown_frame_fails() {
+        let test_config = get_test_config();
+        let store = Store::new(test_config.config);
+        let frame = get_completed_test_frame();
+
+        let result = store.update_frame(&frame);
+
+        assert!(matches!(result, Err(StoreError::UnknownId(_))));
+        assert!(store.get_all_frames().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update_frame() {
+        let test_config = get_test_config();
+        let store = Store::new(test_config.config);
+        let mut frame = get_completed_test_frame();
+        store.insert_frame(&frame).expect("Failed to save frame");
+
+        let start = chrono::Local
+            .with_ymd_and_hms(2020, 1, 1, 10, 0, 0)
+            .unwrap();
+        let end = chrono::Local
+            .with_ymd_and_hms(2020, 1, 1, 11, 0, 0)
+            .unwrap();
+        frame.set_times(start, end);
+        store.update_frame(&frame).expect("Failed to update frame");
+
+        let frames = store.get_all_frames().unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].end(), end);
+    }
+
+    #[test]
+    fn test_cached_store_reads_changes_of_other_stores() {
+        let test_config = get_test_config();
+        let cached = Store::with_cache(test_config.config.clone());
+        let other = Store::new(test_config.config);
+        cached
+            .insert_frame(&get_completed_test_frame())
+            .expect("Failed to save frame");
+        assert_eq!(cached.get_all_frames().unwrap().len(), 1);
+
+        other
+            .insert_frame(&get_completed_test_frame_with_project(
+                NonEmptyString::new("other").unwrap().into(),
+            ))
+            .expect("Failed to save frame");
+
+        assert_eq!(cached.get_all_frames().unwrap().len(), 2);
+    }
 }
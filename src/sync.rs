@@ -0,0 +1,255 @@
+// Sync of frames with a Crick server (https://github.com/TailorDev/crick), like `watson sync`
+//
+
+use std::{fmt::Display, path::Path};
+
+use chrono::{DateTime, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    common::NonEmptyString,
+    config::BackendSettings,
+    frame::{CompletedFrame, Frame, ProjectName},
+};
+
+#[derive(Debug)]
+pub enum SyncError {
+    /// The request could not be sent or the server answered with an error
+    Http(String),
+    /// The server answered with an unexpected status code
+    UnexpectedStatus(u16),
+    /// The answer of the server could not be read
+    InvalidResponse(String),
+    /// The `last_sync` file could not be read or written
+    LastSync(String),
+}
+
+impl Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncError::Http(details) => write!(f, "Request to the backend failed: {}", details),
+            SyncError::UnexpectedStatus(status) => {
+                write!(f, "Unexpected status code {} from the backend", status)
+            }
+            SyncError::InvalidResponse(details) => {
+                write!(f, "Invalid response from the backend: {}", details)
+            }
+            SyncError::LastSync(details) => write!(f, "Invalid last_sync file: {}", details),
+        }
+    }
+}
+
+/// A frame as sent to and received from the Crick API
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteFrame {
+    /// The id of the frame as UUID URN, e.g. `urn:uuid:0123...`
+    id: String,
+    begin_at: DateTime<Utc>,
+    end_at: DateTime<Utc>,
+    project: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl RemoteFrame {
+    /// Convert a local frame. Returns `None` if the id of the frame is not a UUID, as Crick only
+    /// accepts UUIDs as id.
+    fn from_frame(frame: &CompletedFrame) -> Option<Self> {
+        let id = Uuid::try_parse(frame.frame().id()).ok()?;
+        Some(Self {
+            id: id.urn().to_string(),
+            begin_at: frame.frame().start().with_timezone(&Utc),
+            end_at: frame.end().with_timezone(&Utc),
+            project: frame.frame().project().as_str().to_string(),
+            tags: frame
+                .frame()
+                .tags()
+                .iter()
+                .map(|t| t.as_str().to_string())
+                .collect(),
+        })
+    }
+
+    /// Convert into a local frame. As Crick does not know when a frame was edited, the last edit
+    /// time is set to now, like watson does.
+    fn into_frame(self) -> Result<CompletedFrame, String> {
+        let id = Uuid::try_parse(&self.id)
+            .map_err(|e| format!("Invalid id {}: {}", self.id, e))?
+            .simple()
+            .to_string();
+        let project = ProjectName::new(&self.project)
+            .ok_or_else(|| format!("Frame {} with invalid project \"{}\"", id, self.project))?;
+        let tags = self
+            .tags
+            .iter()
+            .filter_map(|tag| NonEmptyString::new(tag))
+            .collect();
+        CompletedFrame::from_frame(Frame::new(
+            project,
+            Some(id),
+            Some(self.begin_at.with_timezone(&Local)),
+            Some(self.end_at.with_timezone(&Local)),
+            tags,
+            None,
+        ))
+        .ok_or_else(|| "Frame without end".to_string())
+    }
+}
+
+/// Client for the frames endpoint of a Crick server
+pub struct CrickClient {
+    /// The URL of the frames endpoint
+    frames_url: String,
+    token: String,
+}
+
+impl CrickClient {
+    pub fn new(backend: &BackendSettings) -> Self {
+        Self {
+            frames_url: format!("{}/frames/", backend.url.trim_end_matches('/')),
+            token: backend.token.clone(),
+        }
+    }
+
+    fn authorization(&self) -> String {
+        format!("Token {}", self.token)
+    }
+
+    /// Get all frames which were changed on the server since `last_sync`
+    pub fn pull(&self, last_sync: DateTime<Utc>) -> Result<Vec<CompletedFrame>, SyncError> {
+        let mut response = ureq::get(&self.frames_url)
+            .query("last_sync", last_sync.to_rfc3339())
+            .header("Authorization", self.authorization())
+            .call()
+            .map_err(|e| SyncError::Http(e.to_string()))?;
+        if response.status() != 200 {
+            return Err(SyncError::UnexpectedStatus(response.status().as_u16()));
+        }
+        let frames: Vec<RemoteFrame> = response
+            .body_mut()
+            .read_json()
+            .map_err(|e| SyncError::InvalidResponse(e.to_string()))?;
+        frames
+            .into_iter()
+            .map(|frame| frame.into_frame().map_err(SyncError::InvalidResponse))
+            .collect()
+    }
+
+    /// Send the frames to the server. Frames with an id which is not a UUID are not sent, they
+    /// are returned instead.
+    pub fn push<'a>(
+        &self,
+        frames: &[&'a CompletedFrame],
+    ) -> Result<Vec<&'a CompletedFrame>, SyncError> {
+        let mut remote_frames = Vec::new();
+        let mut skipped = Vec::new();
+        for frame in frames {
+            match RemoteFrame::from_frame(frame) {
+                Some(remote_frame) => remote_frames.push(remote_frame),
+                None => skipped.push(*frame),
+            }
+        }
+        let response = ureq::post(&self.frames_url)
+            .header("Authorization", self.authorization())
+            .send_json(&remote_frames)
+            .map_err(|e| SyncError::Http(e.to_string()))?;
+        if response.status() != 201 {
+            return Err(SyncError::UnexpectedStatus(response.status().as_u16()));
+        }
+        Ok(skipped)
+    }
+}
+
+/// Read the time of the last sync, which watson stores as timestamp in seconds.
+/// Returns the beginning of the epoch if there was no sync yet.
+pub fn read_last_sync(path: &Path) -> Result<DateTime<Utc>, SyncError> {
+    if !path.exists() {
+        return Ok(DateTime::UNIX_EPOCH);
+    }
+    let content = std::fs::read_to_string(path).map_err(|e| SyncError::LastSync(e.to_string()))?;
+    let timestamp: i64 =
+        serde_json::from_str(content.trim()).map_err(|e| SyncError::LastSync(e.to_string()))?;
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .ok_or_else(|| SyncError::LastSync(format!("Invalid timestamp {}", timestamp)))
+}
+
+pub fn write_last_sync(path: &Path, last_sync: DateTime<Utc>) -> Result<(), SyncError> {
+    std::fs::write(path, last_sync.timestamp().to_string())
+        .map_err(|e| SyncError::LastSync(e.to_string()))
+}
+
+/// The frames which were edited since the last sync and thus have to be pushed
+pub fn frames_to_push(frames: &[CompletedFrame], last_sync: DateTime<Utc>) -> Vec<&CompletedFrame> {
+    frames
+        .iter()
+        .filter(|frame| frame.frame().last_edit() > last_sync)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_frame(id: &str, last_edit: i64) -> CompletedFrame {
+        let start = Local.timestamp_opt(1700000000, 0).unwrap();
+        CompletedFrame::from_frame(Frame::new(
+            NonEmptyString::new("project").unwrap().into(),
+            Some(id.to_string()),
+            Some(start),
+            Some(start + chrono::Duration::hours(1)),
+            vec![NonEmptyString::new("tag").unwrap()],
+            Local.timestamp_opt(last_edit, 0).single(),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_remote_frame_roundtrip() {
+        let frame = create_test_frame("0123456789abcdef0123456789abcdef", 1700000000);
+        let remote = RemoteFrame::from_frame(&frame).unwrap();
+        assert_eq!(remote.id, "urn:uuid:01234567-89ab-cdef-0123-456789abcdef");
+
+        let converted = remote.into_frame().unwrap();
+        assert_eq!(converted.frame().id(), frame.frame().id());
+        assert_eq!(converted.frame().start(), frame.frame().start());
+        assert_eq!(converted.end(), frame.end());
+        assert_eq!(converted.frame().tags()[0].as_str(), "tag");
+    }
+
+    #[test]
+    fn test_remote_frame_project_without_colors() {
+        let frame = create_test_frame("0123456789abcdef0123456789abcdef", 1700000000);
+        // Colors are on when syncing from a terminal
+        colored::control::set_override(true);
+        let remote = RemoteFrame::from_frame(&frame);
+        colored::control::unset_override();
+        assert_eq!(remote.unwrap().project, "project");
+    }
+
+    #[test]
+    fn test_remote_frame_requires_uuid() {
+        assert!(RemoteFrame::from_frame(&create_test_frame("abc", 1700000000)).is_none());
+    }
+
+    #[test]
+    fn test_frames_to_push() {
+        let frames = vec![create_test_frame("a", 100), create_test_frame("b", 300)];
+        let last_sync = Utc.timestamp_opt(200, 0).unwrap();
+        let push = frames_to_push(&frames, last_sync);
+        assert_eq!(push.len(), 1);
+        assert_eq!(push[0].frame().id(), "b");
+    }
+
+    #[test]
+    fn test_last_sync_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("last_sync");
+        assert_eq!(read_last_sync(&path).unwrap(), DateTime::UNIX_EPOCH);
+
+        let now = Utc.timestamp_opt(1700000000, 0).unwrap();
+        write_last_sync(&path, now).unwrap();
+        assert_eq!(read_last_sync(&path).unwrap(), now);
+    }
+}
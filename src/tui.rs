@@ -0,0 +1,594 @@
+// The full-screen dashboard of `watsup tui`, showing the current frame with a live timer, the frames
+// of today and this week and the time per project
+//
+
+use std::io;
+
+use chrono::{DateTime, Duration, Local};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+    layout::{Constraint, Layout},
+    style::{Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table},
+};
+
+use crate::{
+    cli::Command,
+    common::NonEmptyString,
+    frame::{CompletedFrame, FrameStore, ProjectName},
+    log::{FrameLog, beginning_of_day, beginning_of_week, format_duration},
+    state::{OngoingFrame, StateStoreBackend},
+};
+
+/// How often the dashboard is redrawn without input, to update the timer of the current frame
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The number of matching projects shown below the project input
+const PROJECT_SUGGESTIONS: usize = 5;
+
+const KEY_HELP: &str =
+    "s start  x stop  p pause/resume  r restart  e edit  d delete  tab today/week  q quit";
+
+fn format_tags(tags: &[NonEmptyString]) -> String {
+    tags.iter()
+        .map(|tag| format!(" +{}", tag.as_str()))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    Today,
+    Week,
+}
+
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Normal,
+    /// Typing the project of a new frame
+    StartProject(String),
+    /// Asking whether to delete the frame with the id, or to cancel the current frame if `None`
+    ConfirmDelete(Option<String>),
+}
+
+/// What the event loop does after a key was pressed
+#[derive(Debug)]
+enum Action {
+    None,
+    Execute(Command),
+    Quit,
+}
+
+/// A row of the frame list which can be selected
+#[derive(Debug, PartialEq)]
+enum Selection {
+    Current,
+    /// A completed frame with the id
+    Frame(String),
+}
+
+struct Dashboard {
+    ongoing: Option<OngoingFrame>,
+    /// The frames of this week, ordered by start time
+    frames: Vec<CompletedFrame>,
+    projects: Vec<ProjectName>,
+    view: View,
+    /// The index of the selected row, counting only selectable rows
+    selected: usize,
+    mode: Mode,
+    /// The result of the last action, shown instead of the key help
+    message: Option<String>,
+}
+
+impl Dashboard {
+    fn new() -> Self {
+        Self {
+            ongoing: None,
+            frames: Vec::new(),
+            projects: Vec::new(),
+            view: View::Today,
+            selected: 0,
+            mode: Mode::Normal,
+            message: None,
+        }
+    }
+
+    /// Load the current frame, the frames of this week and the projects from the store
+    fn refresh<T: FrameStore + StateStoreBackend>(
+        &mut self,
+        store: &T,
+        now: DateTime<Local>,
+    ) -> Result<(), String> {
+        self.frames = store
+            .get_frames(beginning_of_week(now), now)
+            .map_err(|e| e.to_string())?;
+        self.ongoing = store.get().map_err(|e| e.to_string())?;
+        self.projects = store.get_projects().map_err(|e| e.to_string())?;
+        self.selected = self
+            .selected
+            .min(self.selectable(now).len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// The frames shown in the current view
+    fn visible_frames(&self, now: DateTime<Local>) -> Vec<CompletedFrame> {
+        let from = match self.view {
+            View::Today => beginning_of_day(now),
+            View::Week => beginning_of_week(now),
+        };
+        self.frames
+            .iter()
+            .filter(|frame| *frame.frame().start() >= from)
+            .cloned()
+            .collect()
+    }
+
+    /// The selectable rows in the order they are shown, the current frame first
+    fn selectable(&self, now: DateTime<Local>) -> Vec<Selection> {
+        let visible = self.visible_frames(now);
+        let frames = FrameLog::new(&visible)
+            .days()
+            .into_iter()
+            .flat_map(|day| day.frames)
+            .map(|frame| Selection::Frame(frame.frame().id().to_string()));
+        self.ongoing
+            .iter()
+            .map(|_| Selection::Current)
+            .chain(frames)
+            .collect()
+    }
+
+    /// The projects containing the input, ignoring case
+    fn matching_projects(&self, input: &str) -> Vec<&ProjectName> {
+        let input = input.to_lowercase();
+        let mut projects: Vec<&ProjectName> = self
+            .projects
+            .iter()
+            .filter(|project| project.as_str().to_lowercase().contains(&input))
+            .collect();
+        // Projects starting with the input first
+        projects.sort_by_key(|project| !project.as_str().to_lowercase().starts_with(&input));
+        projects
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, now: DateTime<Local>) -> Action {
+        if key.kind != KeyEventKind::Press {
+            return Action::None;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_key(key.code, now),
+            Mode::StartProject(mut input) => match key.code {
+                KeyCode::Enter if !input.trim().is_empty() => Action::Execute(Command::Start {
+                    project: Some(input.trim().to_string()),
+                    tags: Vec::new(),
+                    no_gap: false,
+                }),
+                KeyCode::Enter | KeyCode::Esc => Action::None,
+                KeyCode::Tab => {
+                    if let Some(project) = self.matching_projects(&input).first() {
+                        input = project.as_str().to_string();
+                    }
+                    self.mode = Mode::StartProject(input);
+                    Action::None
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.mode = Mode::StartProject(input);
+                    Action::None
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.mode = Mode::StartProject(input);
+                    Action::None
+                }
+                _ => {
+                    self.mode = Mode::StartProject(input);
+                    Action::None
+                }
+            },
+            Mode::ConfirmDelete(id) => match (key.code, id) {
+                (KeyCode::Char('y'), Some(id)) => {
+                    Action::Execute(Command::Remove { id, force: true })
+                }
+                (KeyCode::Char('y'), None) => Action::Execute(Command::Cancel),
+                _ => {
+                    self.message = Some("Nothing deleted".to_string());
+                    Action::None
+                }
+            },
+        }
+    }
+
+    fn handle_normal_key(&mut self, code: KeyCode, now: DateTime<Local>) -> Action {
+        let (current_selected, selected_id) =
+            match self.selectable(now).into_iter().nth(self.selected) {
+                Some(Selection::Current) => (true, None),
+                Some(Selection::Frame(id)) => (false, Some(id)),
+                None => (false, None),
+            };
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let rows = self.selectable(now).len();
+                self.selected = (self.selected + 1).min(rows.saturating_sub(1));
+                Action::None
+            }
+            KeyCode::Tab => {
+                self.view = match self.view {
+                    View::Today => View::Week,
+                    View::Week => View::Today,
+                };
+                self.selected = 0;
+                Action::None
+            }
+            KeyCode::Char('s') => {
+                self.mode = Mode::StartProject(String::new());
+                Action::None
+            }
+            KeyCode::Char('x') => Action::Execute(Command::Stop { at: None }),
+            KeyCode::Char('p') => match &self.ongoing {
+                Some(ongoing) if ongoing.is_paused() => Action::Execute(Command::Resume),
+                _ => Action::Execute(Command::Pause),
+            },
+            KeyCode::Char('r') => Action::Execute(Command::Restart { id: selected_id }),
+            KeyCode::Char('e') if current_selected || selected_id.is_some() => {
+                Action::Execute(Command::Edit {
+                    id: selected_id,
+                    from: None,
+                    to: None,
+                    project: None,
+                    format: None,
+                })
+            }
+            KeyCode::Char('d') if current_selected || selected_id.is_some() => {
+                self.mode = Mode::ConfirmDelete(selected_id);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn render(&self, frame: &mut Frame, now: DateTime<Local>) {
+        let [current_area, body_area, input_area, footer_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(match self.mode {
+                Mode::StartProject(_) => 3 + PROJECT_SUGGESTIONS as u16,
+                _ => 0,
+            }),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [frames_area, totals_area] =
+            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(body_area);
+
+        let elapsed: Duration = self
+            .ongoing
+            .iter()
+            .flat_map(|ongoing| ongoing.complete(now))
+            .map(|f| f.duration())
+            .sum();
+        let current = match &self.ongoing {
+            Some(ongoing) => {
+                let state = match ongoing.is_paused() {
+                    true => " (paused)",
+                    false => "",
+                };
+                Line::from(vec![
+                    ongoing.project().as_str().magenta().bold(),
+                    format_tags(ongoing.tags()).into(),
+                    format!(
+                        "  started {}  {}{}",
+                        ongoing.start().format("%H:%M"),
+                        format_duration(elapsed),
+                        state
+                    )
+                    .into(),
+                ])
+            }
+            None => Line::from("No project started".dim()),
+        };
+        frame.render_widget(
+            Paragraph::new(current).block(Block::bordered().title(" Current ")),
+            current_area,
+        );
+
+        let visible = self.visible_frames(now);
+        let mut items = Vec::new();
+        let mut selected_item = None;
+        let mut selectable = 0;
+        let mut select_item = |items: &mut Vec<ListItem>, item: ListItem<'static>| {
+            if selectable == self.selected {
+                selected_item = Some(items.len());
+            }
+            selectable += 1;
+            items.push(item);
+        };
+        if let Some(ongoing) = &self.ongoing {
+            select_item(
+                &mut items,
+                ListItem::new(format!(
+                    "  {:8.8}  {} to {:5}  {:>8}  {}{}",
+                    "current",
+                    ongoing.start().format("%H:%M"),
+                    "now",
+                    format_duration(elapsed),
+                    ongoing.project().as_str(),
+                    format_tags(ongoing.tags())
+                )),
+            );
+        }
+        for day in FrameLog::new(&visible).days() {
+            items.push(ListItem::new(Line::from(
+                format!(
+                    "{} ({})",
+                    day.day.format("%A %d %B %Y"),
+                    format_duration(day.total())
+                )
+                .bold(),
+            )));
+            for completed in day.frames {
+                select_item(
+                    &mut items,
+                    ListItem::new(format!(
+                        "  {:8.8}  {} to {:5}  {:>8}  {}{}",
+                        completed.frame().id(),
+                        completed.frame().start().format("%H:%M"),
+                        completed.end().format("%H:%M"),
+                        format_duration(completed.duration()),
+                        completed.frame().project().as_str(),
+                        format_tags(completed.frame().tags())
+                    )),
+                );
+            }
+        }
+        let title = match self.view {
+            View::Today => " Today ",
+            View::Week => " This week ",
+        };
+        let mut list_state = ListState::default().with_selected(selected_item);
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(title))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            frames_area,
+            &mut list_state,
+        );
+
+        // The totals include the current frame up to now
+        let mut week = self.frames.clone();
+        if let Some(ongoing) = &self.ongoing {
+            week.extend(ongoing.complete(now));
+        }
+        let totals = FrameLog::new(&week).project_totals();
+        let rows: Vec<Row> = totals
+            .nodes()
+            .into_iter()
+            .map(|(depth, project)| {
+                Row::new(vec![
+                    format!("{}{}", "  ".repeat(depth), project.segment()),
+                    format_duration(project.total()),
+                ])
+            })
+            .collect();
+        frame.render_widget(
+            Table::new(rows, [Constraint::Min(10), Constraint::Length(9)])
+                .block(Block::bordered().title(" Projects this week ")),
+            totals_area,
+        );
+
+        if let Mode::StartProject(input) = &self.mode {
+            let mut lines = vec![Line::from(format!("> {}", input))];
+            lines.extend(
+                self.matching_projects(input)
+                    .into_iter()
+                    .take(PROJECT_SUGGESTIONS)
+                    .map(|project| Line::from(format!("  {}", project.as_str()).dim())),
+            );
+            frame.render_widget(
+                Paragraph::new(lines)
+                    .block(Block::bordered().title(" Start project (tab completes) ")),
+                input_area,
+            );
+        }
+
+        let footer = match (&self.mode, &self.message) {
+            (Mode::ConfirmDelete(Some(id)), _) => format!("Delete frame {:.8}? [y/N]", id),
+            (Mode::ConfirmDelete(None), _) => "Cancel the current frame? [y/N]".to_string(),
+            (_, Some(message)) => message.clone(),
+            (_, None) => KEY_HELP.to_string(),
+        };
+        frame.render_widget(Paragraph::new(footer), footer_area);
+    }
+}
+
+/// Leave the dashboard to run a command, so it can print its output, open the editor or ask
+/// questions on the normal screen
+fn suspend() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)
+}
+
+fn resume(terminal: &mut DefaultTerminal) -> io::Result<()> {
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    terminal.clear()
+}
+
+fn event_loop<T: FrameStore + StateStoreBackend>(
+    terminal: &mut DefaultTerminal,
+    store: &T,
+    mut execute_command: impl FnMut(&Command) -> Result<(), String>,
+) -> io::Result<()> {
+    let mut dashboard = Dashboard::new();
+    if let Err(e) = dashboard.refresh(store, Local::now()) {
+        dashboard.message = Some(format!("Error: {}", e));
+    }
+    loop {
+        let now = Local::now();
+        terminal.draw(|frame| dashboard.render(frame, now))?;
+        if !event::poll(REFRESH_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        dashboard.message = None;
+        match dashboard.handle_key(key, now) {
+            Action::None => {}
+            Action::Quit => return Ok(()),
+            Action::Execute(command) => {
+                suspend()?;
+                let result = execute_command(&command);
+                resume(terminal)?;
+                if let Err(e) = result.and_then(|_| dashboard.refresh(store, Local::now())) {
+                    dashboard.message = Some(format!("Error: {}", e));
+                }
+            }
+        }
+    }
+}
+
+/// Show the dashboard until it is quit. The frames are read from the store, all changes are made
+/// by `execute_command`.
+pub fn run<T: FrameStore + StateStoreBackend>(
+    store: &T,
+    execute_command: impl FnMut(&Command) -> Result<(), String>,
+) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, store, execute_command);
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+    use crate::{frame::Frame, stores::in_memory_store::InMemoryStore};
+
+    fn now() -> DateTime<Local> {
+        // A Wednesday
+        Local.with_ymd_and_hms(2025, 1, 8, 12, 0, 0).unwrap()
+    }
+
+    fn create_test_frame(id: &str, project: &str, start: DateTime<Local>) -> CompletedFrame {
+        CompletedFrame::from_frame(Frame::new(
+            NonEmptyString::new(project).unwrap().into(),
+            Some(id.to_string()),
+            Some(start),
+            Some(start + Duration::minutes(30)),
+            vec![NonEmptyString::new("tag").unwrap()],
+            None,
+        ))
+        .unwrap()
+    }
+
+    fn create_dashboard() -> Dashboard {
+        let store = InMemoryStore::new();
+        let monday = Local.with_ymd_and_hms(2025, 1, 6, 9, 0, 0).unwrap();
+        store
+            .insert_frame(&create_test_frame("monday", "docs", monday))
+            .unwrap();
+        store
+            .insert_frame(&create_test_frame(
+                "today",
+                "code",
+                now() - Duration::hours(2),
+            ))
+            .unwrap();
+        let mut dashboard = Dashboard::new();
+        dashboard.refresh(&store, now()).unwrap();
+        dashboard
+    }
+
+    fn press(dashboard: &mut Dashboard, code: KeyCode) -> Action {
+        dashboard.handle_key(KeyEvent::from(code), now())
+    }
+
+    fn render(dashboard: &Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal
+            .draw(|frame| dashboard.render(frame, now()))
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(100)
+            .map(|line| line.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_views_show_frames_of_today_and_week() {
+        let mut dashboard = create_dashboard();
+        let screen = render(&dashboard);
+        assert!(screen.contains("No project started"));
+        assert!(screen.contains("  today     10:00 to 10:30   0:30:00  code +tag"));
+        assert!(!screen.contains("monday"));
+        assert!(screen.contains("docs"), "Totals include the whole week");
+
+        press(&mut dashboard, KeyCode::Tab);
+        let screen = render(&dashboard);
+        assert!(screen.contains("This week"));
+        assert!(screen.contains("Monday 06 January 2025 (0:30:00)"));
+    }
+
+    #[test]
+    fn test_keys_execute_commands_for_selected_frame() {
+        let mut dashboard = create_dashboard();
+        press(&mut dashboard, KeyCode::Tab);
+        // The frames of today are shown first
+        press(&mut dashboard, KeyCode::Down);
+        assert!(matches!(
+            press(&mut dashboard, KeyCode::Char('r')),
+            Action::Execute(Command::Restart { id: Some(id) }) if id == "monday"
+        ));
+        assert!(matches!(
+            press(&mut dashboard, KeyCode::Char('e')),
+            Action::Execute(Command::Edit { id: Some(id), .. }) if id == "monday"
+        ));
+
+        press(&mut dashboard, KeyCode::Char('d'));
+        assert!(render(&dashboard).contains("Delete frame monday? [y/N]"));
+        assert!(matches!(
+            press(&mut dashboard, KeyCode::Char('y')),
+            Action::Execute(Command::Remove { id, force: true }) if id == "monday"
+        ));
+        assert!(matches!(
+            press(&mut dashboard, KeyCode::Char('q')),
+            Action::Quit
+        ));
+    }
+
+    #[test]
+    fn test_start_completes_project() {
+        let mut dashboard = create_dashboard();
+        press(&mut dashboard, KeyCode::Char('s'));
+        press(&mut dashboard, KeyCode::Char('D'));
+        press(&mut dashboard, KeyCode::Tab);
+        assert_eq!(dashboard.mode, Mode::StartProject("docs".to_string()));
+        assert!(matches!(
+            press(&mut dashboard, KeyCode::Enter),
+            Action::Execute(Command::Start { project, no_gap: false, .. }) if project.as_deref() == Some("docs")
+        ));
+        assert_eq!(dashboard.mode, Mode::Normal);
+    }
+}
@@ -0,0 +1,146 @@
+// Integration tests of `watsup daemon` and of the CLI using the running daemon
+//
+#![cfg(unix)]
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tempfile::TempDir;
+
+/// The watson directory inside the given home directory, see `Config::default`
+fn watson_dir(home: &Path) -> PathBuf {
+    match std::env::consts::OS {
+        "macos" => home.join("Library/Application Support/watson"),
+        _ => home.join(".config/watson"),
+    }
+}
+
+/// A daemon running with its own home and runtime directory, killed when dropped
+struct Daemon {
+    process: Child,
+    home: TempDir,
+    runtime_dir: TempDir,
+}
+
+impl Daemon {
+    fn start() -> Self {
+        Self::start_with_frames("[]")
+    }
+
+    /// Start the daemon with the given content of the frames file
+    fn start_with_frames(frames: &str) -> Self {
+        let home = TempDir::new().unwrap();
+        std::fs::create_dir_all(watson_dir(home.path())).unwrap();
+        std::fs::write(watson_dir(home.path()).join("frames"), frames).unwrap();
+        let runtime_dir = TempDir::new().unwrap();
+        let process = Command::new(env!("CARGO_BIN_EXE_watsup"))
+            .arg("daemon")
+            .env("HOME", home.path())
+            .env("XDG_RUNTIME_DIR", runtime_dir.path())
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        let daemon = Self {
+            process,
+            home,
+            runtime_dir,
+        };
+        for _ in 0..100 {
+            if UnixStream::connect(daemon.socket()).is_ok() {
+                return daemon;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("Daemon did not start listening");
+    }
+
+    fn socket(&self) -> PathBuf {
+        self.runtime_dir.path().join("watsup.sock")
+    }
+
+    /// Run watsup with the environment of the daemon and return its output
+    fn watsup(&self, args: &[&str]) -> String {
+        let output = Command::new(env!("CARGO_BIN_EXE_watsup"))
+            .args(args)
+            .env("HOME", self.home.path())
+            .env("XDG_RUNTIME_DIR", self.runtime_dir.path())
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// Send a raw JSON-RPC request and return the response
+    fn call(&self, request: &str) -> serde_json::Value {
+        let mut stream = UnixStream::connect(self.socket()).unwrap();
+        writeln!(stream, "{}", request).unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+#[test]
+fn test_cli_uses_running_daemon() {
+    let daemon = Daemon::start();
+
+    let output = daemon.watsup(&["start", "project", "tag"]);
+    assert!(output.contains("started"), "{}", output);
+    let output = daemon.watsup(&["start", "other"]);
+    assert!(output.contains("Error: Project"), "{}", output);
+    assert_eq!(daemon.watsup(&["status", "-t"]), "tag\n");
+
+    let response = daemon.call(r#"{"jsonrpc": "2.0", "id": 1, "method": "status", "params": {}}"#);
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"]["project"], "project");
+
+    let output = daemon.watsup(&["stop"]);
+    assert!(output.contains("Stopping project"), "{}", output);
+    let frames = std::fs::read_to_string(watson_dir(daemon.home.path()).join("frames")).unwrap();
+    assert!(frames.contains("\"project\""), "{}", frames);
+    assert_eq!(daemon.watsup(&["status", "--quiet"]), "");
+    assert!(daemon.watsup(&["log"]).contains("project"));
+
+    // The commands were sent to the daemon instead of reading the files
+    let log = std::fs::read_to_string(daemon.home.path().join(".config/watsup/log.txt")).unwrap();
+    assert!(log.contains("Executing command with the daemon"));
+}
+
+#[test]
+fn test_daemon_answers_unknown_method_with_error() {
+    let daemon = Daemon::start();
+    let response = daemon.call(r#"{"jsonrpc": "2.0", "id": "a", "method": "pause"}"#);
+    assert_eq!(response["id"], "a");
+    assert_eq!(response["error"]["code"], -32601);
+}
+
+#[test]
+fn test_stop_with_daemon_warns_about_overlaps() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    // A frame which is still running when the started frame stops
+    let frames = serde_json::json!([[now - 3600, now + 3600, "other", "abcd", [], now]]);
+    let daemon = Daemon::start_with_frames(&frames.to_string());
+
+    daemon.watsup(&["start", "project"]);
+    let output = daemon.watsup(&["stop"]);
+
+    assert!(output.contains("Stopping project"), "{}", output);
+    assert!(output.contains("overlaps with frame"), "{}", output);
+}
@@ -0,0 +1,57 @@
+// Integration tests of `watsup edit` with a script as editor
+//
+#![cfg(unix)]
+
+use std::{
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use tempfile::TempDir;
+
+/// The watson directory inside the given home directory, see `Config::default`
+fn watson_dir(home: &Path) -> PathBuf {
+    match std::env::consts::OS {
+        "macos" => home.join("Library/Application Support/watson"),
+        _ => home.join(".config/watson"),
+    }
+}
+
+fn write_script(path: &Path, content: &str) {
+    std::fs::write(path, format!("#!/bin/sh\n{}\n", content)).unwrap();
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn test_failed_save_keeps_the_edit() {
+    let home = TempDir::new().unwrap();
+    let dir = watson_dir(home.path());
+    std::fs::create_dir_all(dir.join("hooks")).unwrap();
+    std::fs::write(
+        dir.join("frames"),
+        r#"[[1700000000, 1700003600, "first", "abc", [], 1700003600]]"#,
+    )
+    .unwrap();
+    write_script(&dir.join("hooks/pre-edit"), "exit 1");
+    let editor = home.path().join("editor");
+    write_script(&editor, "sed -i.bak 's/first/edited/' \"$1\"");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_watsup"))
+        .args(["edit", "abc"])
+        .env("HOME", home.path())
+        .env("EDITOR", &editor)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    let path = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Your edit was saved in "))
+        .unwrap_or_else(|| panic!("Unexpected output {}", stdout));
+    assert!(std::fs::read_to_string(path).unwrap().contains("edited"));
+    let frames = std::fs::read_to_string(dir.join("frames")).unwrap();
+    assert!(frames.contains("first"));
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(format!("{}.bak", path));
+}
@@ -0,0 +1,237 @@
+// Integration tests of `watsup sync --git` and of the auto commit with two clones of a local bare
+// repository
+//
+
+use std::{
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use tempfile::TempDir;
+
+const GIT_IDENTITY: [(&str, &str); 4] = [
+    ("GIT_AUTHOR_NAME", "watsup"),
+    ("GIT_AUTHOR_EMAIL", "watsup@example.com"),
+    ("GIT_COMMITTER_NAME", "watsup"),
+    ("GIT_COMMITTER_EMAIL", "watsup@example.com"),
+];
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .envs(GIT_IDENTITY)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {:?}",
+        args,
+        output
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The watson directory inside the given home directory, see `Config::default`
+fn watson_dir(home: &Path) -> PathBuf {
+    match std::env::consts::OS {
+        "macos" => home.join("Library/Application Support/watson"),
+        _ => home.join(".config/watson"),
+    }
+}
+
+/// Create a home directory whose watson directory is a clone of the remote
+fn clone_home(remote: &Path, config: &str) -> TempDir {
+    let home = TempDir::new().unwrap();
+    let config_dir = home.path().join(".config/watsup");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), config).unwrap();
+    let watson_dir = watson_dir(home.path());
+    std::fs::create_dir_all(watson_dir.parent().unwrap()).unwrap();
+    git(
+        home.path(),
+        &[
+            "clone",
+            "--quiet",
+            remote.to_str().unwrap(),
+            watson_dir.to_str().unwrap(),
+        ],
+    );
+    home
+}
+
+fn watsup(home: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_watsup"))
+        .args(args)
+        .env("HOME", home)
+        .envs(GIT_IDENTITY)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn frame(id: &str, start: i64, project: &str, last_edit: i64) -> serde_json::Value {
+    serde_json::json!([start, start + 3600, project, id, [], last_edit])
+}
+
+fn write_frames(home: &Path, frames: &[serde_json::Value]) {
+    std::fs::write(
+        watson_dir(home).join("frames"),
+        serde_json::to_string_pretty(frames).unwrap(),
+    )
+    .unwrap();
+}
+
+/// The frames of the home directory as (id, project) pairs, ordered by id
+fn read_frames(home: &Path) -> Vec<(String, String)> {
+    let content = std::fs::read_to_string(watson_dir(home).join("frames")).unwrap();
+    let frames: Vec<serde_json::Value> = serde_json::from_str(&content).unwrap();
+    let mut frames: Vec<(String, String)> = frames
+        .iter()
+        .map(|f| (f[3].as_str().unwrap().into(), f[2].as_str().unwrap().into()))
+        .collect();
+    frames.sort();
+    frames
+}
+
+/// A bare remote repository with a first commit of one frame
+fn create_remote() -> TempDir {
+    let remote = TempDir::new().unwrap();
+    git(remote.path(), &["init", "--quiet", "--bare"]);
+    let setup = clone_home(remote.path(), "");
+    write_frames(setup.path(), &[frame("a", 1700000000, "first", 1700003600)]);
+    let dir = watson_dir(setup.path());
+    git(&dir, &["add", "frames"]);
+    git(&dir, &["commit", "--quiet", "-m", "First frame"]);
+    git(&dir, &["push", "--quiet", "origin", "HEAD"]);
+    remote
+}
+
+#[test]
+fn test_diverged_frames_are_merged_by_id() {
+    let remote = create_remote();
+    let laptop = clone_home(remote.path(), "");
+    let desktop = clone_home(remote.path(), "");
+
+    write_frames(
+        laptop.path(),
+        &[
+            frame("a", 1700000000, "first", 1700003600),
+            frame("b", 1700010000, "laptop", 1700013600),
+        ],
+    );
+    watsup(laptop.path(), &["sync", "--git"]);
+
+    // Both sides changed the frames file, which would be a textual conflict
+    write_frames(
+        desktop.path(),
+        &[
+            frame("a", 1700000000, "edited", 1700020000),
+            frame("c", 1700030000, "desktop", 1700033600),
+        ],
+    );
+    let output = watsup(desktop.path(), &["sync", "--git"]);
+    assert!(
+        output.contains("1 added, 0 updated, 0 deleted"),
+        "{}",
+        output
+    );
+
+    watsup(laptop.path(), &["sync", "--git"]);
+
+    let expected = vec![
+        ("a".to_string(), "edited".to_string()),
+        ("b".to_string(), "laptop".to_string()),
+        ("c".to_string(), "desktop".to_string()),
+    ];
+    assert_eq!(read_frames(desktop.path()), expected);
+    assert_eq!(read_frames(laptop.path()), expected);
+}
+
+#[test]
+fn test_auto_commit_after_stop() {
+    let remote = create_remote();
+    let home = clone_home(remote.path(), "[git]\nauto_commit = true\n");
+    let dir = watson_dir(home.path());
+
+    watsup(home.path(), &["start", "project"]);
+    watsup(home.path(), &["stop"]);
+
+    let log = git(&dir, &["log", "--format=%s"]);
+    assert_eq!(log.lines().next(), Some("watsup stop"));
+    assert!(git(&dir, &["status", "--porcelain", "frames"]).is_empty());
+}
+
+#[test]
+fn test_auto_commit_pulls_before_changing_frames() {
+    let remote = create_remote();
+    let laptop = clone_home(remote.path(), "[git]\nauto_commit = true\n");
+    let desktop = clone_home(remote.path(), "");
+
+    write_frames(
+        desktop.path(),
+        &[
+            frame("a", 1700000000, "first", 1700003600),
+            frame("b", 1700010000, "desktop", 1700013600),
+        ],
+    );
+    watsup(desktop.path(), &["sync", "--git"]);
+
+    watsup(laptop.path(), &["start", "project"]);
+    watsup(laptop.path(), &["stop"]);
+
+    let projects: Vec<String> = read_frames(laptop.path())
+        .into_iter()
+        .map(|(_, project)| project)
+        .collect();
+    assert!(projects.contains(&"desktop".to_string()));
+    assert!(projects.contains(&"project".to_string()));
+    let dir = watson_dir(laptop.path());
+    assert_eq!(
+        git(&dir, &["log", "--format=%s"]).lines().next(),
+        Some("watsup stop")
+    );
+    // The commit of the stop is based on the upstream frames
+    git(&dir, &["merge-base", "--is-ancestor", "@{u}", "HEAD"]);
+}
+
+#[test]
+fn test_auto_commit_of_api_changes() {
+    let remote = create_remote();
+    let home = clone_home(remote.path(), "[git]\nauto_commit = true\n");
+    let mut server = Command::new(env!("CARGO_BIN_EXE_watsup"))
+        .args(["serve", "--bind", "127.0.0.1:0"])
+        .env("HOME", home.path())
+        .envs(GIT_IDENTITY)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(server.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let url = line
+        .trim()
+        .strip_prefix("Listening on ")
+        .unwrap()
+        .to_string();
+
+    let response = ureq::post(format!("{}/frames", url)).send_json(serde_json::json!({
+        "project": "api",
+        "start": "2025-01-06T09:00:00+01:00",
+        "stop": "2025-01-06T10:00:00+01:00",
+    }));
+    let _ = server.kill();
+    let _ = server.wait();
+    assert_eq!(response.unwrap().status(), 201);
+
+    let dir = watson_dir(home.path());
+    assert_eq!(
+        git(&dir, &["log", "--format=%s"]).lines().next(),
+        Some("watsup serve POST /frames")
+    );
+    assert!(git(&dir, &["status", "--porcelain", "frames"]).is_empty());
+}
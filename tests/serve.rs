@@ -0,0 +1,191 @@
+// Integration tests of the HTTP API of `watsup serve` with a local client
+//
+
+use std::{
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+};
+
+use serde_json::{Value, json};
+use tempfile::TempDir;
+use ureq::Agent;
+
+const TOKEN: &str = "secret";
+
+/// The watson directory inside the given home directory, see `Config::default`
+fn watson_dir(home: &Path) -> PathBuf {
+    match std::env::consts::OS {
+        "macos" => home.join("Library/Application Support/watson"),
+        _ => home.join(".config/watson"),
+    }
+}
+
+/// A server on a free local port, killed when dropped
+struct ApiServer {
+    process: Child,
+    url: String,
+    _home: TempDir,
+}
+
+impl ApiServer {
+    fn start(token: Option<&str>) -> Self {
+        let home = TempDir::new().unwrap();
+        std::fs::create_dir_all(watson_dir(home.path())).unwrap();
+        let mut command = Command::new(env!("CARGO_BIN_EXE_watsup"));
+        command
+            .args(["serve", "--bind", "127.0.0.1:0"])
+            .env("HOME", home.path())
+            .stdout(Stdio::piped());
+        if let Some(token) = token {
+            command.args(["--token", token]);
+        }
+        let mut process = command.spawn().unwrap();
+        let mut line = String::new();
+        BufReader::new(process.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let url = line
+            .trim()
+            .strip_prefix("Listening on ")
+            .unwrap_or_else(|| panic!("Unexpected output {}", line))
+            .to_string();
+        Self {
+            process,
+            url,
+            _home: home,
+        }
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// A client which returns responses with error statuses instead of failing
+fn client() -> Agent {
+    Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+fn authorization() -> String {
+    format!("Bearer {}", TOKEN)
+}
+
+/// Send the request and return the status and the JSON body, `Null` if there is none
+fn send(request: ureq::RequestBuilder<ureq::typestate::WithoutBody>) -> (u16, Value) {
+    let mut response = request
+        .header("Authorization", authorization())
+        .call()
+        .unwrap();
+    let status = response.status().as_u16();
+    let body = response.body_mut().read_to_string().unwrap();
+    (status, serde_json::from_str(&body).unwrap_or(Value::Null))
+}
+
+fn send_json(
+    request: ureq::RequestBuilder<ureq::typestate::WithBody>,
+    body: &Value,
+) -> (u16, Value) {
+    let mut response = request
+        .header("Authorization", authorization())
+        .send_json(body)
+        .unwrap();
+    let status = response.status().as_u16();
+    (status, response.body_mut().read_json().unwrap())
+}
+
+#[test]
+fn test_frames_projects_tags_and_report() {
+    let server = ApiServer::start(Some(TOKEN));
+    let client = client();
+    let frames_url = format!("{}/frames", server.url);
+
+    let frame = json!({
+        "project": "project",
+        "start": "2025-01-06T09:00:00+01:00",
+        "stop": "2025-01-06T10:00:00+01:00",
+        "tags": ["a"],
+    });
+    let (status, created) = send_json(client.post(&frames_url), &frame);
+    assert_eq!(status, 201);
+    let id = created["id"].as_str().unwrap().to_string();
+    let frame_url = format!("{}/{}", frames_url, id);
+
+    let (status, _) = send_json(
+        client.post(&frames_url),
+        &json!({
+            "project": "other",
+            "start": "2025-01-07T09:00:00+01:00",
+            "stop": "2025-01-07T09:30:00+01:00",
+        }),
+    );
+    assert_eq!(status, 201);
+
+    let (status, frames) = send(client.get(&frames_url).query("project", "project"));
+    assert_eq!(status, 200);
+    assert_eq!(frames.as_array().unwrap().len(), 1);
+    assert_eq!(frames[0]["id"], id.as_str());
+
+    let mut updated = frame.clone();
+    updated["tags"] = json!(["a", "b"]);
+    let (status, frame) = send_json(client.put(&frame_url), &updated);
+    assert_eq!(status, 200);
+    assert_eq!(frame["tags"], json!(["a", "b"]));
+    let (_, frame) = send(client.get(&frame_url));
+    assert_eq!(frame["tags"], json!(["a", "b"]));
+
+    let (_, projects) = send(client.get(format!("{}/projects", server.url)));
+    assert_eq!(projects, json!(["other", "project"]));
+    let (_, tags) = send(client.get(format!("{}/tags", server.url)));
+    assert_eq!(tags, json!(["a", "b"]));
+    let (_, current) = send(client.get(format!("{}/current", server.url)));
+    assert_eq!(current, Value::Null);
+
+    let (status, report) = send(
+        client
+            .get(format!("{}/report", server.url))
+            .query("from", "2025-01-01")
+            .query("to", "2025-01-31"),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(report["time"], 5400);
+    assert_eq!(report["projects"][1]["name"], "project");
+    assert_eq!(report["projects"][1]["time"], 3600);
+
+    let (status, _) = send(client.delete(&frame_url));
+    assert_eq!(status, 204);
+    let (status, error) = send(client.get(&frame_url));
+    assert_eq!(status, 404);
+    assert!(error["error"].as_str().unwrap().contains(&id));
+}
+
+#[test]
+fn test_requests_without_token_are_rejected() {
+    let server = ApiServer::start(Some(TOKEN));
+    let response = client()
+        .get(format!("{}/frames", server.url))
+        .call()
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.headers().get("WWW-Authenticate").unwrap(),
+        "Bearer"
+    );
+}
+
+#[test]
+fn test_server_without_token_answers_everyone() {
+    let server = ApiServer::start(None);
+    let mut response = client()
+        .get(format!("{}/projects", server.url))
+        .call()
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.body_mut().read_to_string().unwrap(), "[]");
+}
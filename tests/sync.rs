@@ -0,0 +1,208 @@
+// Integration tests of `watsup sync` against a local stub of the Crick API
+//
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// A request received by the stub server
+#[derive(Debug, Clone)]
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Start a HTTP server on a free local port, which answers every request with the response
+/// returned by `respond` for it. Returns the base URL and the received requests.
+fn start_stub_server(
+    respond: impl Fn(&Request) -> (u16, String) + Send + 'static,
+) -> (String, Arc<Mutex<Vec<Request>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = requests.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or_default().to_string();
+            let path = parts.next().unwrap_or_default().to_string();
+            let mut headers = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((key, value)) = line.split_once(':') {
+                    headers.push((key.trim().to_string(), value.trim().to_string()));
+                }
+            }
+            let mut request = Request {
+                method,
+                path,
+                headers,
+                body: String::new(),
+            };
+            let length: usize = request
+                .header("content-length")
+                .map(|l| l.parse().unwrap())
+                .unwrap_or(0);
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            request.body = String::from_utf8(body).unwrap();
+
+            let (status, body) = respond(&request);
+            received.lock().unwrap().push(request);
+            write!(
+                stream,
+                "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+    (url, requests)
+}
+
+/// The watson directory inside the given home directory, see `Config::default`
+fn watson_dir(home: &Path) -> PathBuf {
+    match std::env::consts::OS {
+        "macos" => home.join("Library/Application Support/watson"),
+        _ => home.join(".config/watson"),
+    }
+}
+
+/// Create a home directory with the given frames and a config pointing to the backend
+fn setup_home(backend_url: &str, frames: &str) -> tempfile::TempDir {
+    let home = tempfile::TempDir::new().unwrap();
+    let config_dir = home.path().join(".config/watsup");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.toml"),
+        format!("[backend]\nurl = \"{}\"\ntoken = \"secret\"\n", backend_url),
+    )
+    .unwrap();
+    let watson_dir = watson_dir(home.path());
+    std::fs::create_dir_all(&watson_dir).unwrap();
+    std::fs::write(watson_dir.join("frames"), frames).unwrap();
+    home
+}
+
+fn run_sync(home: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_watsup"))
+        .arg("sync")
+        .env("HOME", home)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+const LOCAL_FRAME_ID: &str = "0123456789abcdef0123456789abcdef";
+const REMOTE_FRAMES: &str = r#"[{
+    "id": "urn:uuid:fedcba98-7654-3210-fedc-ba9876543210",
+    "begin_at": "2023-11-15T10:00:00+00:00",
+    "end_at": "2023-11-15T11:00:00+00:00",
+    "project": "remote",
+    "tags": ["tag"]
+}]"#;
+
+#[test]
+fn test_sync_pulls_and_pushes_frames() {
+    let (url, requests) = start_stub_server(|request| match request.method.as_str() {
+        "GET" => (200, REMOTE_FRAMES.to_string()),
+        _ => (201, "[]".to_string()),
+    });
+    let home = setup_home(
+        &url,
+        &format!(
+            r#"[[1700000000, 1700003600, "local", "{}", [], 1700003600]]"#,
+            LOCAL_FRAME_ID
+        ),
+    );
+
+    let output = run_sync(home.path());
+
+    assert!(
+        output.contains("Pulled 1 frames, pushed 1 frames"),
+        "{}",
+        output
+    );
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method, "GET");
+    assert!(
+        requests[0]
+            .path
+            .starts_with("/api/frames/?last_sync=1970-01-01")
+    );
+    assert_eq!(requests[0].header("authorization"), Some("Token secret"));
+    assert_eq!(requests[1].method, "POST");
+    assert_eq!(requests[1].path, "/api/frames/");
+    let pushed: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+    assert_eq!(
+        pushed[0]["id"],
+        "urn:uuid:01234567-89ab-cdef-0123-456789abcdef"
+    );
+    assert_eq!(pushed[0]["project"], "local");
+
+    let watson_dir = watson_dir(home.path());
+    let frames = std::fs::read_to_string(watson_dir.join("frames")).unwrap();
+    assert!(frames.contains("fedcba9876543210fedcba9876543210"));
+    assert!(watson_dir.join("last_sync").exists());
+}
+
+#[test]
+fn test_second_sync_pushes_nothing() {
+    let (url, requests) = start_stub_server(|request| match request.method.as_str() {
+        "GET" => (200, REMOTE_FRAMES.to_string()),
+        _ => (201, "[]".to_string()),
+    });
+    let home = setup_home(&url, "[]");
+
+    run_sync(home.path());
+    let output = run_sync(home.path());
+
+    // The pulled frame is unchanged, so it is neither updated nor pushed back
+    assert!(
+        output.contains("Pulled 0 frames, pushed 0 frames"),
+        "{}",
+        output
+    );
+    let requests = requests.lock().unwrap();
+    assert!(requests.iter().all(|request| request.method == "GET"));
+}
+
+#[test]
+fn test_sync_with_rejected_token_keeps_last_sync() {
+    let (url, _) = start_stub_server(|_| (401, "{}".to_string()));
+    let home = setup_home(&url, "[]");
+
+    let output = run_sync(home.path());
+
+    assert!(output.starts_with("Error: Sync failed"), "{}", output);
+    assert!(!watson_dir(home.path()).join("last_sync").exists());
+}